@@ -0,0 +1,140 @@
+use std::time::Duration;
+
+use actix_web::web;
+use bot::tracker::{CourierHealthRequest, SubscriptionStatsRequest};
+use telbot_hyper::types::message::{Message, SendMessage};
+
+use crate::{
+    locale::Text,
+    render::Reply,
+    state::AppState,
+    telegram::{self, escape, sender_id},
+};
+
+const BROADCAST_INTERVAL: Duration = Duration::from_millis(50);
+
+pub async fn handle_admin_command(
+    app: &web::Data<AppState>,
+    message: &Message,
+    label: &str,
+    rest: &str,
+) {
+    let chat_id = message.chat.id;
+    let locale = app.settings.locale(sender_id(message));
+
+    let text = match label {
+        "/stats" => {
+            let mut client = app.tracker();
+            match client
+                .get_subscription_stats(tonic::Request::new(SubscriptionStatsRequest {}))
+                .await
+            {
+                Ok(response) => {
+                    let stats = response.into_inner();
+                    format!(
+                        "{} {}\n{} {}\n{} {}",
+                        locale.text(Text::StatsUsers),
+                        app.settings.user_ids().len(),
+                        locale.text(Text::StatsTrackingChats),
+                        stats.users,
+                        locale.text(Text::StatsSubscriptions),
+                        stats.subscriptions
+                    )
+                }
+                Err(_) => locale.text(Text::ServerError).to_string(),
+            }
+        }
+        "/courier_health" => {
+            let mut client = app.tracker();
+            match client
+                .get_courier_health(tonic::Request::new(CourierHealthRequest {}))
+                .await
+            {
+                Ok(response) => {
+                    let couriers = response.into_inner().couriers;
+                    if couriers.is_empty() {
+                        locale.text(Text::CourierHealthEmpty).to_string()
+                    } else {
+                        couriers
+                            .iter()
+                            .map(|courier| {
+                                let mark = if courier.failure > courier.success {
+                                    "🔴"
+                                } else if courier.failure > 0 {
+                                    "🟡"
+                                } else {
+                                    "🟢"
+                                };
+                                let mut line = format!(
+                                    "{} {}: ✅ {} / ❌ {}",
+                                    mark, courier.id, courier.success, courier.failure
+                                );
+                                if let Some(last_error) = &courier.last_error {
+                                    line = format!("{}\n  {}", line, last_error);
+                                }
+                                line
+                            })
+                            .collect::<Vec<String>>()
+                            .join("\n")
+                    }
+                }
+                Err(_) => locale.text(Text::ServerError).to_string(),
+            }
+        }
+        "/broadcast" => {
+            if rest.is_empty() {
+                locale.text(Text::BroadcastUsage).to_string()
+            } else {
+                // Sending to every user takes a while, so report the counts when done.
+                let (app, text) = (app.clone(), rest.to_string());
+                actix_web::rt::spawn(async move {
+                    let (sent, failed) = broadcast(&app, &text).await;
+                    let report = format!(
+                        "{} ✅ {} / ❌ {}",
+                        locale.text(Text::BroadcastSent),
+                        sent,
+                        failed
+                    );
+                    telegram::send_reply(&app, chat_id, Reply::text(escape(report))).await;
+                });
+                locale.text(Text::BroadcastStarted).to_string()
+            }
+        }
+        "/ban" | "/unban" => match rest.parse::<i64>() {
+            Ok(user_id) if app.is_admin(user_id) => locale.text(Text::BanUsage).to_string(),
+            Ok(user_id) => {
+                let banned = label == "/ban";
+                app.settings
+                    .update(user_id, |settings| settings.banned = banned)
+                    .ok();
+                if banned {
+                    format!("{} {}", locale.text(Text::UserBanned), user_id)
+                } else {
+                    format!("{} {}", locale.text(Text::UserUnbanned), user_id)
+                }
+            }
+            Err(_) => locale.text(Text::BanUsage).to_string(),
+        },
+        _ => return,
+    };
+
+    telegram::send_reply(app, chat_id, Reply::text(escape(text))).await;
+}
+
+async fn broadcast(app: &AppState, text: &str) -> (usize, usize) {
+    let mut sent = 0;
+    let mut failed = 0;
+
+    for user_id in app.settings.user_ids() {
+        if app.settings.get(user_id).banned {
+            continue;
+        }
+        match app.api.send_json(&SendMessage::new(user_id, text)).await {
+            Ok(_) => sent += 1,
+            Err(_) => failed += 1,
+        }
+        tokio::time::sleep(BROADCAST_INTERVAL).await;
+    }
+
+    (sent, failed)
+}
@@ -0,0 +1,94 @@
+use std::fmt;
+
+use actix_web::web;
+use image::GenericImageView;
+use telbot_hyper::types::file::{GetFile, PhotoSize};
+
+use crate::state::AppState;
+
+/// Telegram serves files up to 20 MB to bots, anything larger is not a photo we sent for.
+const MAX_PHOTO_SIZE: usize = 20 * 1024 * 1024;
+
+enum DownloadError {
+    Request(reqwest::Error),
+    TooLarge,
+}
+
+impl fmt::Display for DownloadError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DownloadError::Request(err) => write!(f, "{}", err),
+            DownloadError::TooLarge => write!(f, "file exceeds {} bytes", MAX_PHOTO_SIZE),
+        }
+    }
+}
+
+impl From<reqwest::Error> for DownloadError {
+    fn from(err: reqwest::Error) -> Self {
+        // File URLs embed the bot token, keep it out of the logs.
+        DownloadError::Request(err.without_url())
+    }
+}
+
+pub async fn read_photo(app: &AppState, photo: &[PhotoSize]) -> Vec<String> {
+    let largest = match photo.iter().max_by_key(|size| size.width * size.height) {
+        Some(largest) => largest,
+        None => return Vec::new(),
+    };
+
+    let file = match app
+        .api
+        .send_json(&GetFile::new(largest.file_id.clone()))
+        .await
+    {
+        Ok(file) => file,
+        Err(_) => return Vec::new(),
+    };
+    let file_path = match file.file_path {
+        Some(file_path) => file_path,
+        None => return Vec::new(),
+    };
+
+    let bytes = match download(&app.file_url(&file_path)).await {
+        Ok(bytes) => bytes,
+        Err(err) => {
+            tracing::warn!("Failed to download photo: {}", err);
+            return Vec::new();
+        }
+    };
+
+    web::block(move || decode(&bytes)).await.unwrap_or_default()
+}
+
+async fn download(url: &str) -> Result<Vec<u8>, DownloadError> {
+    let mut response = reqwest::get(url).await?.error_for_status()?;
+    if response.content_length().unwrap_or_default() > MAX_PHOTO_SIZE as u64 {
+        return Err(DownloadError::TooLarge);
+    }
+
+    let mut bytes = Vec::new();
+    while let Some(chunk) = response.chunk().await? {
+        if bytes.len() + chunk.len() > MAX_PHOTO_SIZE {
+            return Err(DownloadError::TooLarge);
+        }
+        bytes.extend_from_slice(&chunk);
+    }
+    Ok(bytes)
+}
+
+fn decode(bytes: &[u8]) -> Vec<String> {
+    let image = match image::load_from_memory(bytes) {
+        Ok(image) => image,
+        Err(_) => return Vec::new(),
+    };
+    let (width, height) = image.dimensions();
+
+    rxing::helpers::detect_multiple_in_luma(image.to_luma8().into_raw(), width, height)
+        .map(|results| {
+            results
+                .iter()
+                .map(|result| result.getText().to_string())
+                .collect()
+        })
+        .unwrap_or_default()
+}
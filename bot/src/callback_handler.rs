@@ -0,0 +1,62 @@
+use telbot_hyper::types::{
+    chat::GetChatAdministrators,
+    query::{AnswerCallbackQuery, CallbackQuery},
+};
+use tracing::warn;
+
+use crate::{
+    conversation,
+    state::AppState,
+    telegram::{self, is_private_chat, MarkdownV2},
+};
+
+const INLINE_ACTIONS: [&str; 2] = ["show_all:", "history:"];
+
+pub async fn handle_callback_query(app: &AppState, callback_query: &CallbackQuery) {
+    let answer = AnswerCallbackQuery::new(callback_query.id.clone());
+    if let Err(err) = app.api.send_json(&answer).await {
+        warn!("Failed to answer callback query: {:?}", err);
+    }
+
+    let data = match &callback_query.data {
+        Some(data) => data,
+        None => return,
+    };
+    let user_id = callback_query.from.id;
+
+    if let Some(message) = &callback_query.message {
+        let chat_id = message.chat.id;
+        // Only stopping a track asks for it, so spare the API call otherwise.
+        let is_chat_admin = data.starts_with("untrack:")
+            && (is_private_chat(message) || is_chat_admin(app, chat_id, user_id).await);
+        let session = telegram::session(app, chat_id, user_id, is_chat_admin);
+
+        if let Some(reply) = conversation::handle_action::<MarkdownV2>(app, &session, data).await {
+            telegram::edit_reply(app, message, reply).await;
+        }
+    } else if let Some(inline_message_id) = &callback_query.inline_message_id {
+        // Inline results only offer the history, and the chat they were sent to is unknown,
+        // so anything touching a tracking list is left alone.
+        if !INLINE_ACTIONS.iter().any(|action| data.starts_with(action)) {
+            return;
+        }
+        let mut session = telegram::session(app, user_id, user_id, false);
+        session.dialogue = false;
+
+        if let Some(reply) = conversation::handle_action::<MarkdownV2>(app, &session, data).await {
+            telegram::edit_inline_reply(app, inline_message_id, reply).await;
+        }
+    }
+}
+
+async fn is_chat_admin(app: &AppState, chat_id: i64, user_id: i64) -> bool {
+    app.api
+        .send_json(&GetChatAdministrators::new(chat_id))
+        .await
+        .map(|administrators| {
+            administrators
+                .iter()
+                .any(|administrator| administrator.user.id == user_id)
+        })
+        .unwrap_or(false)
+}
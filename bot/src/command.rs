@@ -25,6 +25,16 @@ impl<'a> Iterator for Args<'a> {
     }
 }
 
+impl<'a> Args<'a> {
+    pub fn new(inner: &'a str) -> Self {
+        Self { inner }
+    }
+
+    pub fn rest(&self) -> &'a str {
+        self.inner.trim()
+    }
+}
+
 impl<'a> Command<'a> {
     pub fn new(line: &'a str) -> Self {
         let (label_with_username, rest) =
@@ -40,6 +50,12 @@ impl<'a> Command<'a> {
         }
     }
 
+    pub fn is_addressed_to(&self, bot_username: &str) -> bool {
+        self.username
+            .map(|username| username.eq_ignore_ascii_case(bot_username))
+            .unwrap_or(true)
+    }
+
     pub fn args(&self) -> Args<'a> {
         Args { inner: self.rest }
     }
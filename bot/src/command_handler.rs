@@ -1,93 +1,123 @@
-use telbot_hyper::{
-    types::{
-        markup::ParseMode,
-        message::{Message, SendMessage},
-    },
-    Api,
-};
+use actix_web::web;
+use chrono_tz::Tz;
+use telbot_hyper::types::message::Message;
 
 use crate::{
+    admin_handler,
     command::Command,
-    dialogue::{
-        Dialogue, DialogueAnswerKind, DialogueStage, ReceivedTrackingNumberState, StartState,
-        TypeKind,
-    },
-    dialogue_handler,
-    telegram::{self, escape},
+    conversation,
+    locale::{Locale, Text},
+    metrics,
+    render::Reply,
+    state::AppState,
+    telegram::{self, escape, sender_id, MarkdownV2, BOT_USERNAME},
 };
 
-pub async fn handle_command(api: &Api, message: &Message, text: &str) {
+/// Commands only the Telegram front-end offers, on top of [`conversation::COMMANDS`].
+const COMMANDS: &[&str] = &[
+    "/language",
+    "/timezone",
+    "/stats",
+    "/courier_health",
+    "/broadcast",
+    "/ban",
+    "/unban",
+];
+
+pub async fn handle_command(app: &web::Data<AppState>, message: &Message, text: &str) {
     let command = Command::new(text);
+    if let Some(bot_username) = BOT_USERNAME.get() {
+        if !command.is_addressed_to(bot_username) {
+            return;
+        }
+    }
+
+    let chat_id = message.chat.id;
+    let user_id = sender_id(message);
+    // Commands never remove tracks, the untrack button looks up the chat admins itself.
+    let session = telegram::session(app, chat_id, user_id, false);
+    let name = command.label.trim_start_matches('/');
+    if conversation::COMMANDS.contains(&name) {
+        if let Some(reply) =
+            conversation::handle_command::<MarkdownV2>(app, &session, name, command.args()).await
+        {
+            telegram::send_reply(app, chat_id, reply).await;
+        }
+        return;
+    }
+
+    let command_label = if COMMANDS.contains(&command.label) {
+        command.label
+    } else {
+        "other"
+    };
+    metrics::COMMANDS.with_label_values(&[command_label]).inc();
+
+    let settings = session.settings;
+    let locale = settings.locale();
     let mut args = command.args();
     match command.label {
-        "/start" | "/help" => {
-            let help_message = telegram::escape(
-                "/help - 도움말을 볼 수 있어요.\n\
-                /search - 운송장 번호로 택배를 조회할 수 있어요.\n\
-                /track - 운송장 번호로 택배를 추적할 수 있어요.\n\
-                /list - 현재 추적중인 운송장을 관리할 수 있어요.\n\
-                /cancel - 대화를 취소할 수 있어요.",
-            );
-            api.send_json(
-                &SendMessage::new(message.chat.id, help_message)
-                    .with_parse_mode(ParseMode::MarkdownV2),
-            )
-            .await
-            .expect("Failed to send help message");
-        }
-        "/search" => {
-            let stage = if let Some(tracking_number) = args.next() {
-                DialogueStage::ReceivedTrackingNumber(ReceivedTrackingNumberState {
-                    kind: TypeKind::Search,
-                    user_id: message.chat.id,
-                    tracking_number: Some(tracking_number.to_string()),
-                })
-            } else {
-                DialogueStage::Start(StartState {
-                    kind: TypeKind::Search,
-                    user_id: message.chat.id,
-                })
+        "/language" => {
+            let text = match args.next() {
+                Some("auto") => {
+                    app.settings
+                        .update(user_id, |settings| settings.locale = None)
+                        .ok();
+                    app.settings.locale(user_id).text(Text::LanguageChanged)
+                }
+                Some(code) => match Locale::from_code(code) {
+                    Some(locale) => {
+                        app.settings
+                            .update(user_id, |settings| settings.locale = Some(locale))
+                            .ok();
+                        locale.text(Text::LanguageChanged)
+                    }
+                    None => locale.text(Text::LanguageUsage),
+                },
+                None => locale.text(Text::LanguageUsage),
             };
 
-            Dialogue::next(message.chat.id, stage.clone());
-            dialogue_handler::handle_dialogue(
-                api,
-                stage,
-                DialogueAnswerKind::Message(String::new()),
-            )
-            .await;
+            telegram::send_reply(app, chat_id, Reply::text(escape(text))).await;
         }
-        "/track" => {
-            let stage = if let Some(tracking_number) = args.next() {
-                DialogueStage::ReceivedTrackingNumber(ReceivedTrackingNumberState {
-                    kind: TypeKind::Track,
-                    user_id: message.chat.id,
-                    tracking_number: Some(tracking_number.to_string()),
-                })
-            } else {
-                DialogueStage::Start(StartState {
-                    kind: TypeKind::Track,
-                    user_id: message.chat.id,
-                })
+        "/timezone" => {
+            let text = match (args.next(), args.next()) {
+                (Some("original"), Some(toggle @ ("on" | "off"))) => {
+                    let show_original_time = toggle == "on";
+                    app.settings
+                        .update(user_id, |settings| {
+                            settings.show_original_time = show_original_time
+                        })
+                        .ok();
+                    if show_original_time {
+                        locale.text(Text::OriginalTimeShown).to_string()
+                    } else {
+                        locale.text(Text::OriginalTimeHidden).to_string()
+                    }
+                }
+                (Some(name), None) if name != "original" => match name.parse::<Tz>() {
+                    Ok(timezone) => {
+                        app.settings
+                            .update(user_id, |settings| {
+                                settings.timezone = Some(timezone.name().to_string())
+                            })
+                            .ok();
+                        format!("{} {}", locale.text(Text::TimezoneChanged), timezone.name())
+                    }
+                    Err(_) => locale.text(Text::InvalidTimezone).to_string(),
+                },
+                _ => format!(
+                    "{} {}",
+                    locale.text(Text::TimezoneUsage),
+                    settings.timezone().name()
+                ),
             };
 
-            Dialogue::next(message.chat.id, stage.clone());
-            dialogue_handler::handle_dialogue(
-                api,
-                stage,
-                DialogueAnswerKind::Message(String::new()),
-            )
-            .await;
+            telegram::send_reply(app, chat_id, Reply::text(escape(text))).await;
         }
-        "/cancel" => {
-            if Dialogue::exit(message.chat.id) {
-                api.send_json(
-                    &SendMessage::new(message.chat.id, escape("❌ 취소되었어요."))
-                        .with_parse_mode(ParseMode::MarkdownV2),
-                )
-                .await
-                .expect("Failed to send cancel message");
-            }
+        "/stats" | "/courier_health" | "/broadcast" | "/ban" | "/unban"
+            if app.is_admin(user_id) =>
+        {
+            admin_handler::handle_admin_command(app, message, command.label, args.rest()).await;
         }
         _ => {}
     };
@@ -0,0 +1,494 @@
+use std::{
+    env, fmt, fs, io,
+    net::SocketAddr,
+    path::{Path, PathBuf},
+    str::FromStr,
+    time::Duration,
+};
+
+use serde::Deserialize;
+
+const DEFAULT_CONFIG_PATH: &str = "bot.toml";
+
+#[derive(Debug)]
+pub enum ConfigError {
+    Read(PathBuf, io::Error),
+    Parse(PathBuf, toml::de::Error),
+    Env(&'static str, String),
+    Invalid(String),
+}
+
+impl fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ConfigError::Read(path, err) => write!(f, "can't read {}: {}", path.display(), err),
+            ConfigError::Parse(path, err) => write!(f, "can't parse {}: {}", path.display(), err),
+            ConfigError::Env(name, value) => {
+                write!(f, "env {} has invalid value {:?}", name, value)
+            }
+            ConfigError::Invalid(message) => f.write_str(message),
+        }
+    }
+}
+
+impl std::error::Error for ConfigError {}
+
+#[derive(Debug, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Config {
+    pub port: u16,
+    /// Serves `/metrics` here, apart from the public port, when set.
+    pub metrics_addr: Option<SocketAddr>,
+    pub bot_token: String,
+    pub mode: BotMode,
+    pub admin_ids: Vec<i64>,
+    pub settings_path: Option<PathBuf>,
+    pub log_format: LogFormat,
+    pub grpc: GrpcConfig,
+    pub webhook: WebhookConfig,
+    pub polling: PollingConfig,
+    pub discord: DiscordConfig,
+    pub slack: SlackConfig,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            port: 8080,
+            metrics_addr: None,
+            bot_token: String::new(),
+            mode: BotMode::default(),
+            admin_ids: Vec::new(),
+            settings_path: None,
+            log_format: LogFormat::default(),
+            grpc: GrpcConfig::default(),
+            webhook: WebhookConfig::default(),
+            polling: PollingConfig::default(),
+            discord: DiscordConfig::default(),
+            slack: SlackConfig::default(),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum BotMode {
+    Webhook,
+    Polling,
+}
+
+impl Default for BotMode {
+    fn default() -> Self {
+        BotMode::Webhook
+    }
+}
+
+impl FromStr for BotMode {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "webhook" => Ok(BotMode::Webhook),
+            "polling" => Ok(BotMode::Polling),
+            _ => Err(()),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LogFormat {
+    Text,
+    Json,
+}
+
+impl Default for LogFormat {
+    fn default() -> Self {
+        LogFormat::Text
+    }
+}
+
+impl FromStr for LogFormat {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "text" => Ok(LogFormat::Text),
+            "json" => Ok(LogFormat::Json),
+            _ => Err(()),
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct GrpcConfig {
+    pub addr: String,
+    pub api_key: Option<String>,
+    pub connect_timeout_secs: u64,
+    pub request_timeout_secs: u64,
+    pub tls: GrpcTlsConfig,
+}
+
+impl Default for GrpcConfig {
+    fn default() -> Self {
+        Self {
+            addr: String::new(),
+            api_key: None,
+            connect_timeout_secs: 5,
+            request_timeout_secs: 30,
+            tls: GrpcTlsConfig::default(),
+        }
+    }
+}
+
+impl GrpcConfig {
+    pub fn connect_timeout(&self) -> Duration {
+        Duration::from_secs(self.connect_timeout_secs)
+    }
+
+    pub fn request_timeout(&self) -> Duration {
+        Duration::from_secs(self.request_timeout_secs)
+    }
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct GrpcTlsConfig {
+    pub ca_path: Option<PathBuf>,
+    pub cert_path: Option<PathBuf>,
+    pub key_path: Option<PathBuf>,
+    pub domain: Option<String>,
+}
+
+impl GrpcTlsConfig {
+    pub fn is_enabled(&self) -> bool {
+        self.ca_path.is_some() || self.cert_path.is_some() || self.domain.is_some()
+    }
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct WebhookConfig {
+    pub url: Option<String>,
+    pub path: String,
+    pub secret: Option<String>,
+}
+
+impl Default for WebhookConfig {
+    fn default() -> Self {
+        Self {
+            url: None,
+            path: "/tg_webhook".to_string(),
+            secret: None,
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct PollingConfig {
+    pub timeout_secs: u32,
+    pub retry_delay_secs: u64,
+}
+
+impl Default for PollingConfig {
+    fn default() -> Self {
+        Self {
+            timeout_secs: 30,
+            retry_delay_secs: 5,
+        }
+    }
+}
+
+impl PollingConfig {
+    pub fn retry_delay(&self) -> Duration {
+        Duration::from_secs(self.retry_delay_secs)
+    }
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct DiscordConfig {
+    pub path: String,
+    pub public_key: Option<String>,
+    pub application_id: Option<String>,
+    /// Registers the slash commands on startup when set.
+    pub bot_token: Option<String>,
+}
+
+impl Default for DiscordConfig {
+    fn default() -> Self {
+        Self {
+            path: "/discord/interactions".to_string(),
+            public_key: None,
+            application_id: None,
+            bot_token: None,
+        }
+    }
+}
+
+impl DiscordConfig {
+    pub fn is_enabled(&self) -> bool {
+        self.public_key.is_some()
+    }
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct SlackConfig {
+    pub commands_path: String,
+    pub interactions_path: String,
+    pub signing_secret: Option<String>,
+}
+
+impl Default for SlackConfig {
+    fn default() -> Self {
+        Self {
+            commands_path: "/slack/commands".to_string(),
+            interactions_path: "/slack/interactions".to_string(),
+            signing_secret: None,
+        }
+    }
+}
+
+impl SlackConfig {
+    pub fn is_enabled(&self) -> bool {
+        self.signing_secret.is_some()
+    }
+}
+
+impl Config {
+    /// Reads `CONFIG_PATH` (or `bot.toml` if it exists), then applies env overrides.
+    pub fn load() -> Result<Self, ConfigError> {
+        let mut config = match env_var("CONFIG_PATH") {
+            Some(path) => Self::from_file(path)?,
+            None if Path::new(DEFAULT_CONFIG_PATH).exists() => {
+                Self::from_file(DEFAULT_CONFIG_PATH)?
+            }
+            None => Self::default(),
+        };
+        config.apply_env()?;
+        config.validate()?;
+        Ok(config)
+    }
+
+    pub fn from_file<P: AsRef<Path>>(path: P) -> Result<Self, ConfigError> {
+        let path = path.as_ref().to_path_buf();
+        let content =
+            fs::read_to_string(&path).map_err(|err| ConfigError::Read(path.clone(), err))?;
+        toml::from_str(&content).map_err(|err| ConfigError::Parse(path, err))
+    }
+
+    fn apply_env(&mut self) -> Result<(), ConfigError> {
+        if let Some(port) = parse_env("PORT")? {
+            self.port = port;
+        }
+        if let Some(addr) = parse_env("BOT_METRICS_ADDR")? {
+            self.metrics_addr = Some(addr);
+        }
+        if let Some(token) = env_var("BOT_TOKEN") {
+            self.bot_token = token;
+        }
+        if let Some(mode) = parse_env("BOT_MODE")? {
+            self.mode = mode;
+        }
+        if let Some(ids) = env_var("ADMIN_IDS") {
+            self.admin_ids = ids
+                .split(',')
+                .map(str::trim)
+                .filter(|id| !id.is_empty())
+                .map(|id| id.parse())
+                .collect::<Result<_, _>>()
+                .map_err(|_| ConfigError::Env("ADMIN_IDS", ids.clone()))?;
+        }
+        if let Some(path) = env_var("SETTINGS_PATH") {
+            self.settings_path = Some(path.into());
+        }
+        if let Some(format) = parse_env("LOG_FORMAT")? {
+            self.log_format = format;
+        }
+        if let Some(addr) = env_var("GRPC_ADDR") {
+            self.grpc.addr = addr;
+        }
+        if let Some(api_key) = env_var("GRPC_API_KEY") {
+            self.grpc.api_key = Some(api_key);
+        }
+        if let Some(path) = env_var("GRPC_TLS_CA_PATH") {
+            self.grpc.tls.ca_path = Some(path.into());
+        }
+        if let Some(path) = env_var("GRPC_TLS_CERT_PATH") {
+            self.grpc.tls.cert_path = Some(path.into());
+        }
+        if let Some(path) = env_var("GRPC_TLS_KEY_PATH") {
+            self.grpc.tls.key_path = Some(path.into());
+        }
+        if let Some(domain) = env_var("GRPC_TLS_DOMAIN") {
+            self.grpc.tls.domain = Some(domain);
+        }
+        if let Some(url) = env_var("WEBHOOK_URL") {
+            self.webhook.url = Some(url);
+        }
+        if let Some(path) = env_var("WEBHOOK_PATH") {
+            self.webhook.path = path;
+        }
+        if let Some(secret) = env_var("WEBHOOK_SECRET") {
+            self.webhook.secret = Some(secret);
+        }
+        if let Some(public_key) = env_var("DISCORD_PUBLIC_KEY") {
+            self.discord.public_key = Some(public_key);
+        }
+        if let Some(application_id) = env_var("DISCORD_APPLICATION_ID") {
+            self.discord.application_id = Some(application_id);
+        }
+        if let Some(token) = env_var("DISCORD_BOT_TOKEN") {
+            self.discord.bot_token = Some(token);
+        }
+        if let Some(secret) = env_var("SLACK_SIGNING_SECRET") {
+            self.slack.signing_secret = Some(secret);
+        }
+        Ok(())
+    }
+
+    fn validate(&self) -> Result<(), ConfigError> {
+        if self.bot_token.is_empty() {
+            return Err(ConfigError::Invalid("bot_token is not set".to_string()));
+        }
+        if self.grpc.addr.is_empty() {
+            return Err(ConfigError::Invalid("grpc.addr is not set".to_string()));
+        }
+        if self.grpc.tls.cert_path.is_some() != self.grpc.tls.key_path.is_some() {
+            return Err(ConfigError::Invalid(
+                "grpc.tls.cert_path and grpc.tls.key_path must be set together".to_string(),
+            ));
+        }
+        if self.grpc.connect_timeout_secs == 0 || self.grpc.request_timeout_secs == 0 {
+            return Err(ConfigError::Invalid(
+                "grpc timeouts must be greater than 0".to_string(),
+            ));
+        }
+        if !self.webhook.path.starts_with('/') {
+            return Err(ConfigError::Invalid(
+                "webhook.path must start with '/'".to_string(),
+            ));
+        }
+        if let Some(secret) = &self.webhook.secret {
+            // Telegram rejects any other secret_token, which would fail setWebhook at startup.
+            let allowed = |c: char| c.is_ascii_alphanumeric() || c == '_' || c == '-';
+            if secret.is_empty() || secret.len() > 256 || !secret.chars().all(allowed) {
+                return Err(ConfigError::Invalid(
+                    "webhook.secret must be 1-256 characters of A-Z, a-z, 0-9, _ and -".to_string(),
+                ));
+            }
+        }
+        if let Some(public_key) = &self.discord.public_key {
+            if public_key.len() != 64 || !public_key.chars().all(|c| c.is_ascii_hexdigit()) {
+                return Err(ConfigError::Invalid(
+                    "discord.public_key must be 64 hex characters".to_string(),
+                ));
+            }
+            if self.discord.application_id.is_none() {
+                return Err(ConfigError::Invalid(
+                    "discord.application_id is not set".to_string(),
+                ));
+            }
+        }
+        if self.slack.signing_secret.as_deref() == Some("") {
+            return Err(ConfigError::Invalid(
+                "slack.signing_secret must not be empty".to_string(),
+            ));
+        }
+        for path in [
+            &self.discord.path,
+            &self.slack.commands_path,
+            &self.slack.interactions_path,
+        ] {
+            if !path.starts_with('/') {
+                return Err(ConfigError::Invalid(format!(
+                    "{} must start with '/'",
+                    path
+                )));
+            }
+        }
+        Ok(())
+    }
+}
+
+fn env_var(name: &str) -> Option<String> {
+    env::var(name).ok().filter(|value| !value.is_empty())
+}
+
+fn parse_env<T: FromStr>(name: &'static str) -> Result<Option<T>, ConfigError> {
+    match env_var(name) {
+        Some(value) => value
+            .parse()
+            .map(Some)
+            .map_err(|_| ConfigError::Env(name, value)),
+        None => Ok(None),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(content: &str) -> Config {
+        toml::from_str(&format!(
+            "bot_token = \"token\"\n[grpc]\naddr = \"http://localhost:50051\"\n{}",
+            content
+        ))
+        .unwrap()
+    }
+
+    #[test]
+    fn accepts_the_minimal_config() {
+        assert!(parse("").validate().is_ok());
+        assert!(Config::default().validate().is_err());
+    }
+
+    #[test]
+    fn keeps_metrics_off_the_public_port() {
+        assert_eq!(parse("").metrics_addr, None);
+        let config: Config = toml::from_str(
+            "bot_token = \"token\"\nmetrics_addr = \"127.0.0.1:9091\"\n[grpc]\naddr = \"http://localhost:50051\"",
+        )
+        .unwrap();
+        assert_eq!(config.metrics_addr, Some(([127, 0, 0, 1], 9091).into()));
+    }
+
+    #[test]
+    fn validates_the_webhook_secret() {
+        let secret = |secret: &str| parse(&format!("[webhook]\nsecret = \"{}\"", secret));
+        assert!(secret("s3cret_token-1").validate().is_ok());
+        assert!(secret(&"a".repeat(256)).validate().is_ok());
+        assert!(secret("").validate().is_err());
+        assert!(secret(&"a".repeat(257)).validate().is_err());
+        assert!(secret("has space").validate().is_err());
+        assert!(secret("colon:secret").validate().is_err());
+    }
+
+    #[test]
+    fn rejects_invalid_settings() {
+        assert!(parse("connect_timeout_secs = 0").validate().is_err());
+        assert!(parse("[grpc.tls]\ncert_path = \"cert.pem\"")
+            .validate()
+            .is_err());
+        assert!(parse("[webhook]\npath = \"webhook\"").validate().is_err());
+        assert!(parse("[webhook]\nsecret = \"\"").validate().is_err());
+        assert!(parse("[slack]\nsigning_secret = \"\"").validate().is_err());
+        assert!(parse("[discord]\npublic_key = \"abcd\"")
+            .validate()
+            .is_err());
+        assert!(
+            parse(&format!("[discord]\npublic_key = \"{}\"", "ab".repeat(32)))
+                .validate()
+                .is_err()
+        );
+        assert!(parse(&format!(
+            "[discord]\npublic_key = \"{}\"\napplication_id = \"1\"",
+            "ab".repeat(32)
+        ))
+        .validate()
+        .is_ok());
+    }
+}
@@ -0,0 +1,490 @@
+use bot::tracker::{
+    AddTrackRequest, ExtractTrackingNumbersRequest, RemoveTrackRequest, SearchRequest, StatusKind,
+    SupportCouriersRequest, Tags, TrackingListRequest, UpdateTrackRequest,
+};
+
+use crate::{
+    command::Args,
+    dialogue::{Dialogue, DialogueStage, TypeKind},
+    history,
+    locale::{Locale, Text},
+    metrics,
+    render::{
+        create_courier_buttons, create_history_buttons, create_history_pages,
+        create_search_error_buttons, create_search_result, create_simple_tracking_message,
+        create_tracking_list_buttons, create_tracking_list_message, create_tracking_number_buttons,
+        Markup, Reply,
+    },
+    settings::UserSettings,
+    state::AppState,
+};
+
+/// Commands every front-end shares, without the leading slash.
+pub const COMMANDS: &[&str] = &[
+    "start", "help", "search", "track", "list", "label", "note", "tags", "cancel",
+];
+
+/// Who is talking to the bot, as seen by the tracker backend.
+pub struct Session {
+    /// Namespaced tracker user id shared by everyone in the chat.
+    pub user_id: String,
+    pub requested_by: String,
+    pub is_chat_admin: bool,
+    pub settings: UserSettings,
+    /// Whether plain messages reach the bot, so a missing tracking number can be asked for.
+    pub dialogue: bool,
+}
+
+impl Session {
+    fn locale(&self) -> Locale {
+        self.settings.locale()
+    }
+}
+
+fn text<M: Markup>(locale: Locale, text: Text) -> Reply {
+    Reply::text(M::escape(locale.text(text)))
+}
+
+pub fn help<M: Markup>(session: &Session) -> Reply {
+    text::<M>(session.locale(), Text::Help)
+}
+
+/// Runs a shared command, or returns `None` if `name` is not one of [`COMMANDS`]
+/// or there is nothing to answer.
+pub async fn handle_command<M: Markup>(
+    app: &AppState,
+    session: &Session,
+    name: &str,
+    mut args: Args<'_>,
+) -> Option<Reply> {
+    if !COMMANDS.contains(&name) {
+        return None;
+    }
+    metrics::COMMANDS
+        .with_label_values(&[&format!("/{}", name)])
+        .inc();
+
+    let locale = session.locale();
+    let reply = match name {
+        "start" | "help" => help::<M>(session),
+        "search" | "track" => {
+            let kind = if name == "search" {
+                TypeKind::Search
+            } else {
+                TypeKind::Track
+            };
+            Dialogue::exit(&session.user_id, &session.requested_by);
+            let rest = args.rest();
+            match (args.next(), args.next()) {
+                // Courier ids look like kr.cjlogistics, anything else is part of the text.
+                (Some(tracking_number), Some(courier_id)) if courier_id.contains('.') => {
+                    run::<M>(app, session, kind, courier_id, tracking_number).await
+                }
+                (None, _) => {
+                    if session.dialogue {
+                        Dialogue::next(
+                            &session.user_id,
+                            &session.requested_by,
+                            DialogueStage::AwaitingTrackingNumber(kind),
+                        );
+                    }
+                    let prompt = match kind {
+                        TypeKind::Search => Text::EnterSearchTrackingNumber,
+                        TypeKind::Track => Text::EnterTrackTrackingNumber,
+                    };
+                    text::<M>(locale, prompt)
+                }
+                _ => resolve_tracking_number::<M>(app, session, kind, rest).await,
+            }
+        }
+        "list" => tracking_list::<M>(app, session, None).await,
+        "label" | "note" | "tags" => update_track::<M>(app, session, name, args).await,
+        _ => {
+            if !Dialogue::exit(&session.user_id, &session.requested_by) {
+                return None;
+            }
+            text::<M>(locale, Text::Cancelled)
+        }
+    };
+    Some(reply)
+}
+
+/// Answers a pending prompt, or returns `None` if the message is not part of a dialogue.
+pub async fn handle_message<M: Markup>(
+    app: &AppState,
+    session: &Session,
+    text: &str,
+) -> Option<Reply> {
+    let kind = match Dialogue::get(&session.user_id, &session.requested_by)? {
+        DialogueStage::AwaitingTrackingNumber(kind) => kind,
+    };
+    Dialogue::exit(&session.user_id, &session.requested_by);
+    Some(resolve_tracking_number::<M>(app, session, kind, text).await)
+}
+
+/// Handles the callback data of a button rendered by [`crate::render`].
+pub async fn handle_action<M: Markup>(
+    app: &AppState,
+    session: &Session,
+    data: &str,
+) -> Option<Reply> {
+    if let Some(rest) = data.strip_prefix("retry:") {
+        let (courier_id, tracking_number) = rest.split_once(':')?;
+        Some(search::<M>(app, session, courier_id, tracking_number).await)
+    } else if let Some(rest) = data.strip_prefix("add:") {
+        let (courier_id, tracking_number) = rest.split_once(':')?;
+        Some(add_track::<M>(app, session, courier_id, tracking_number).await)
+    } else if let Some(rest) = data.strip_prefix("untrack:") {
+        let (courier_id, tracking_number) = rest.split_once(':')?;
+        Some(remove_track::<M>(app, session, courier_id, tracking_number).await)
+    } else if let Some(rest) = data.strip_prefix("show_all:") {
+        let (courier_id, tracking_number) = rest.split_once(':')?;
+        Some(history::<M>(app, session, courier_id, tracking_number, 0, true).await)
+    } else if let Some(rest) = data.strip_prefix("history:") {
+        let (rest, page) = rest.rsplit_once(':')?;
+        let (courier_id, tracking_number) = rest.split_once(':')?;
+        let page = page.parse().ok()?;
+        Some(history::<M>(app, session, courier_id, tracking_number, page, false).await)
+    } else if let Some(tracking_number) = data.strip_prefix("search:") {
+        Some(select_courier::<M>(app, session, TypeKind::Search, tracking_number).await)
+    } else if let Some(tracking_number) = data.strip_prefix("track:") {
+        Some(select_courier::<M>(app, session, TypeKind::Track, tracking_number).await)
+    } else {
+        None
+    }
+}
+
+async fn search<M: Markup>(
+    app: &AppState,
+    session: &Session,
+    courier_id: &str,
+    tracking_number: &str,
+) -> Reply {
+    let mut client = app.tracker();
+    let request = tonic::Request::new(SearchRequest {
+        courier_id: courier_id.to_string(),
+        tracking_number: tracking_number.to_string(),
+    });
+
+    match client.search(request).await {
+        Ok(response) => create_search_result::<M>(
+            &response.into_inner(),
+            courier_id,
+            tracking_number,
+            &session.settings,
+        ),
+        Err(_) => text::<M>(session.locale(), Text::ServerError),
+    }
+}
+
+/// Asks for the courier, or goes straight on when only one courier accepts the number.
+async fn select_courier<M: Markup>(
+    app: &AppState,
+    session: &Session,
+    kind: TypeKind,
+    tracking_number: &str,
+) -> Reply {
+    let locale = session.locale();
+    let mut client = app.tracker();
+    let request = tonic::Request::new(SupportCouriersRequest {
+        tracking_number: tracking_number.to_string(),
+    });
+
+    let response = match client.get_support_couriers(request).await {
+        Ok(response) => response.into_inner(),
+        Err(_) => return text::<M>(locale, Text::ServerError),
+    };
+
+    match response.couriers.as_slice() {
+        [] => text::<M>(locale, Text::NoSupportedCourier),
+        [courier] => run::<M>(app, session, kind, &courier.id, tracking_number).await,
+        _ => {
+            let prompt = match kind {
+                TypeKind::Search => Text::SelectSearchCourier,
+                TypeKind::Track => Text::SelectTrackCourier,
+            };
+            Reply::with_buttons(
+                M::escape(locale.text(prompt)),
+                create_courier_buttons(&response, |courier_id| {
+                    format!("{}:{}:{}", kind.run_action(), courier_id, tracking_number)
+                }),
+            )
+        }
+    }
+}
+
+async fn run<M: Markup>(
+    app: &AppState,
+    session: &Session,
+    kind: TypeKind,
+    courier_id: &str,
+    tracking_number: &str,
+) -> Reply {
+    match kind {
+        TypeKind::Search => search::<M>(app, session, courier_id, tracking_number).await,
+        TypeKind::Track => add_track::<M>(app, session, courier_id, tracking_number).await,
+    }
+}
+
+/// Finds the tracking numbers in `text` and asks which one is meant if there are several.
+async fn resolve_tracking_number<M: Markup>(
+    app: &AppState,
+    session: &Session,
+    kind: TypeKind,
+    text: &str,
+) -> Reply {
+    let locale = session.locale();
+    let mut client = app.tracker();
+    let request = tonic::Request::new(ExtractTrackingNumbersRequest {
+        text: text.to_string(),
+    });
+
+    let mut candidates = match client.extract_tracking_numbers(request).await {
+        Ok(response) => response.into_inner().candidates,
+        Err(_) => return text::<M>(locale, Text::ServerError),
+    };
+    if candidates.len() > 1 {
+        return Reply::with_buttons(
+            M::escape(locale.text(Text::SelectTrackingNumber)),
+            create_tracking_number_buttons(&candidates, |tracking_number| {
+                format!("{}:{}", kind.as_str(), tracking_number)
+            }),
+        );
+    }
+
+    let tracking_number = match candidates.pop() {
+        Some(candidate) => candidate.tracking_number,
+        None => text
+            .split_whitespace()
+            .next()
+            .unwrap_or_default()
+            .to_string(),
+    };
+    select_courier::<M>(app, session, kind, &tracking_number).await
+}
+
+async fn update_track<M: Markup>(
+    app: &AppState,
+    session: &Session,
+    field: &str,
+    mut args: Args<'_>,
+) -> Reply {
+    let locale = session.locale();
+    let tracking_number = match args.next() {
+        Some(tracking_number) => tracking_number,
+        None => return text::<M>(locale, Text::TrackUpdateUsage),
+    };
+    // The same number may be tracked with several couriers, a courier id after it picks one.
+    let couriers = match tracked_couriers(app, session, tracking_number).await {
+        Ok(couriers) => couriers,
+        Err(_) => return text::<M>(locale, Text::ServerError),
+    };
+    let rest = args.rest();
+    let (first, after_first) = rest.split_once(char::is_whitespace).unwrap_or((rest, ""));
+    let (courier_id, value) = match couriers.as_slice() {
+        _ if couriers.iter().any(|courier_id| courier_id == first) => (first, after_first.trim()),
+        [] => return text::<M>(locale, Text::NotTracking),
+        [courier_id] => (courier_id.as_str(), rest),
+        _ => return text::<M>(locale, Text::TrackUpdateCourierRequired),
+    };
+    let value = value.to_string();
+    let (label, note, tags) = match field {
+        "label" => (Some(value), None, None),
+        "note" => (None, Some(value), None),
+        _ => {
+            let tags = value
+                .split_whitespace()
+                .map(|tag| tag.trim_start_matches('#').to_string())
+                .filter(|tag| !tag.is_empty())
+                .collect();
+            (None, None, Some(Tags { tags }))
+        }
+    };
+
+    let mut client = app.tracker();
+    let request = tonic::Request::new(UpdateTrackRequest {
+        user_id: session.user_id.clone(),
+        courier_id: courier_id.to_string(),
+        tracking_number: tracking_number.to_string(),
+        label,
+        note,
+        tags,
+        email: None,
+    });
+
+    let answer = match client.update_track(request).await {
+        Ok(response) => match response.into_inner().status() {
+            StatusKind::Ok => Text::TrackUpdated,
+            _ => Text::NotTracking,
+        },
+        Err(_) => Text::ServerError,
+    };
+    text::<M>(locale, answer)
+}
+
+/// Ids of the couriers the session tracks `tracking_number` with.
+async fn tracked_couriers(
+    app: &AppState,
+    session: &Session,
+    tracking_number: &str,
+) -> Result<Vec<String>, tonic::Status> {
+    let mut client = app.tracker();
+    let request = tonic::Request::new(TrackingListRequest {
+        user_id: session.user_id.clone(),
+    });
+    let entries = client.tracking_list(request).await?.into_inner().entries;
+    Ok(entries
+        .into_iter()
+        .filter_map(|entry| entry.tracking_info)
+        .filter(|info| info.tracking_number == tracking_number)
+        .map(|info| info.id)
+        .collect())
+}
+
+async fn add_track<M: Markup>(
+    app: &AppState,
+    session: &Session,
+    courier_id: &str,
+    tracking_number: &str,
+) -> Reply {
+    let locale = session.locale();
+    let mut client = app.tracker();
+    let request = tonic::Request::new(AddTrackRequest {
+        user_id: session.user_id.clone(),
+        courier_id: courier_id.to_string(),
+        tracking_number: tracking_number.to_string(),
+        added_by: session.requested_by.clone(),
+        label: None,
+        note: None,
+        tags: Vec::new(),
+        callback_url: None,
+        email: None,
+    });
+
+    let response = match client.add_track(request).await {
+        Ok(response) => response.into_inner(),
+        Err(_) => return text::<M>(locale, Text::ServerError),
+    };
+    match (response.status(), response.response) {
+        (StatusKind::Ok, Some(response)) => Reply::text(format!(
+            "{}\n\n{}",
+            M::escape(locale.text(Text::TrackingStarted)),
+            create_simple_tracking_message::<M>(&response, &session.settings)
+        )),
+        (StatusKind::TrackingAlreadyExists, _) => text::<M>(locale, Text::TrackingAlreadyExists),
+        (_, response) => {
+            let message = match response {
+                Some(response) => create_simple_tracking_message::<M>(&response, &session.settings),
+                None => M::escape(locale.text(Text::NoTrackingInfo)),
+            };
+            Reply::with_buttons(
+                message,
+                create_search_error_buttons(TypeKind::Track, courier_id, tracking_number, locale),
+            )
+        }
+    }
+}
+
+async fn remove_track<M: Markup>(
+    app: &AppState,
+    session: &Session,
+    courier_id: &str,
+    tracking_number: &str,
+) -> Reply {
+    let mut client = app.tracker();
+    let request = tonic::Request::new(RemoveTrackRequest {
+        user_id: session.user_id.clone(),
+        courier_id: courier_id.to_string(),
+        tracking_number: tracking_number.to_string(),
+        requested_by: session.requested_by.clone(),
+        is_chat_admin: session.is_chat_admin,
+    });
+
+    let answer = match client.remove_track(request).await {
+        Ok(response) => match response.into_inner().status() {
+            StatusKind::Ok => Text::TrackingStopped,
+            StatusKind::TrackingNotExists => Text::NotTracking,
+            StatusKind::PermissionDenied => Text::StopTrackingPermissionDenied,
+            _ => Text::ServerError,
+        },
+        Err(_) => Text::ServerError,
+    };
+    tracking_list::<M>(app, session, Some(answer)).await
+}
+
+async fn tracking_list<M: Markup>(
+    app: &AppState,
+    session: &Session,
+    notice: Option<Text>,
+) -> Reply {
+    let locale = session.locale();
+    let mut client = app.tracker();
+    let request = tonic::Request::new(TrackingListRequest {
+        user_id: session.user_id.clone(),
+    });
+
+    let mut reply = match client.tracking_list(request).await {
+        Ok(response) => {
+            let entries = response.into_inner().entries;
+            Reply::with_buttons(
+                create_tracking_list_message::<M>(&entries, &session.settings),
+                create_tracking_list_buttons(&entries, locale),
+            )
+        }
+        Err(_) => text::<M>(locale, Text::ServerError),
+    };
+    if let Some(notice) = notice {
+        reply.text = format!("{}\n\n{}", M::escape(locale.text(notice)), reply.text);
+    }
+    reply
+}
+
+/// Shows a page of the full history, fetching it afresh only when `refresh` is set or the
+/// cached copy has expired.
+async fn history<M: Markup>(
+    app: &AppState,
+    session: &Session,
+    courier_id: &str,
+    tracking_number: &str,
+    page: usize,
+    refresh: bool,
+) -> Reply {
+    let locale = session.locale();
+    let cached = if refresh {
+        None
+    } else {
+        history::get(courier_id, tracking_number)
+    };
+    let info = match cached {
+        Some(info) => info,
+        None => {
+            let mut client = app.tracker();
+            let request = tonic::Request::new(SearchRequest {
+                courier_id: courier_id.to_string(),
+                tracking_number: tracking_number.to_string(),
+            });
+
+            let response = match client.search(request).await {
+                Ok(response) => response.into_inner(),
+                Err(_) => return text::<M>(locale, Text::ServerError),
+            };
+            match (response.status(), &response.tracking_info) {
+                (StatusKind::Ok, Some(info)) => {
+                    history::insert(courier_id, tracking_number, info.clone());
+                    info.clone()
+                }
+                _ => {
+                    return Reply::text(create_simple_tracking_message::<M>(
+                        &response,
+                        &session.settings,
+                    ))
+                }
+            }
+        }
+    };
+
+    let mut pages = create_history_pages::<M>(&info, &session.settings);
+    let page = page.min(pages.len() - 1);
+    let buttons = create_history_buttons(&info, page, pages.len(), locale);
+    Reply::with_buttons(pages.swap_remove(page), buttons)
+}
@@ -2,69 +2,66 @@ use std::{collections::HashMap, sync::RwLock};
 
 use once_cell::sync::Lazy;
 
-static GLOBAL_DATA: Lazy<RwLock<HashMap<i64, DialogueStage>>> =
+static GLOBAL_DATA: Lazy<RwLock<HashMap<(String, String), DialogueStage>>> =
     Lazy::new(|| RwLock::new(HashMap::new()));
 
 pub struct Dialogue;
 
+/// Courier and tracking number choices travel in button callback data,
+/// so the only state kept between messages is an unanswered prompt.
 #[derive(Clone, Debug)]
 pub enum DialogueStage {
-    Start(StartState),
-    ReceivedTrackingNumber(ReceivedTrackingNumberState),
-    SelectedCourier(SelectedCourierState),
+    /// Asked for a tracking number, the next message answers it.
+    AwaitingTrackingNumber(TypeKind),
 }
 
-#[derive(Clone, Debug)]
-pub enum DialogueAnswerKind {
-    Message(String),
-    CallbackQuery(String),
-}
-
-#[derive(Clone, Debug)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub enum TypeKind {
     Search,
     Track,
 }
 
-#[derive(Clone, Debug)]
-pub struct StartState {
-    pub kind: TypeKind,
-    pub user_id: i64,
-}
-
-#[derive(Clone, Debug)]
-pub struct ReceivedTrackingNumberState {
-    pub kind: TypeKind,
-    pub user_id: i64,
-    pub tracking_number: Option<String>,
-}
+impl TypeKind {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            TypeKind::Search => "search",
+            TypeKind::Track => "track",
+        }
+    }
 
-#[derive(Clone, Debug)]
-pub struct SelectedCourierState {
-    pub kind: TypeKind,
-    pub user_id: i64,
-    pub tracking_number: String,
-    pub message_id: i64,
+    /// Callback action that runs the request once the courier is known.
+    pub fn run_action(self) -> &'static str {
+        match self {
+            TypeKind::Search => "retry",
+            TypeKind::Track => "add",
+        }
+    }
 }
 
 impl Dialogue {
-    pub fn get(user_id: i64) -> Option<DialogueStage> {
+    pub fn get(chat_id: &str, user_id: &str) -> Option<DialogueStage> {
         if let Ok(map) = GLOBAL_DATA.read() {
-            map.get(&user_id).map(|stage| stage.clone())
+            map.get(&(chat_id.to_string(), user_id.to_string()))
+                .map(|stage| stage.clone())
         } else {
             None
         }
     }
 
-    pub fn next(user_id: i64, dialogue_stage: DialogueStage) {
+    pub fn next(chat_id: &str, user_id: &str, dialogue_stage: DialogueStage) {
         if let Ok(mut map) = GLOBAL_DATA.write() {
-            map.insert(user_id, dialogue_stage);
+            map.insert((chat_id.to_string(), user_id.to_string()), dialogue_stage);
         }
     }
 
-    pub fn exit(user_id: i64) -> bool {
+    pub fn count() -> usize {
+        GLOBAL_DATA.read().map(|map| map.len()).unwrap_or_default()
+    }
+
+    pub fn exit(chat_id: &str, user_id: &str) -> bool {
         if let Ok(mut map) = GLOBAL_DATA.write() {
-            map.remove(&user_id).is_some()
+            map.remove(&(chat_id.to_string(), user_id.to_string()))
+                .is_some()
         } else {
             false
         }
@@ -0,0 +1,495 @@
+use std::convert::TryFrom;
+
+use actix_web::{web, HttpRequest, HttpResponse};
+use ed25519_dalek::{PublicKey, Signature, Verifier};
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use tracing::{info, warn};
+
+use crate::{
+    command::Args,
+    config::DiscordConfig,
+    conversation::{self, Session},
+    metrics,
+    render::{Button, ButtonAction, Markup, Reply},
+    settings::UserSettings,
+    state::AppState,
+};
+
+const API_URL: &str = "https://discord.com/api/v10";
+const SIGNATURE_HEADER: &str = "X-Signature-Ed25519";
+const TIMESTAMP_HEADER: &str = "X-Signature-Timestamp";
+const MANAGE_MESSAGES: u64 = 1 << 13;
+const MAX_CONTENT_LENGTH: usize = 2000;
+const MAX_ROWS: usize = 5;
+const MAX_BUTTONS_PER_ROW: usize = 5;
+
+static CLIENT: Lazy<reqwest::Client> = Lazy::new(reqwest::Client::new);
+
+pub struct DiscordMarkdown;
+
+impl Markup for DiscordMarkdown {
+    const MESSAGE_LIMIT: usize = 1900;
+
+    fn escape(text: &str) -> String {
+        const ESCAPE: [char; 11] = ['\\', '*', '_', '~', '`', '|', '>', '[', ']', '(', ')'];
+        let mut output = String::new();
+        for c in text.chars() {
+            if ESCAPE.contains(&c) {
+                output.push('\\');
+            }
+            output.push(c);
+        }
+        output
+    }
+
+    fn bold(text: &str) -> String {
+        format!("**{}**", Self::escape(text))
+    }
+}
+
+#[derive(Deserialize)]
+struct Interaction {
+    #[serde(rename = "type")]
+    kind: u8,
+    token: String,
+    application_id: String,
+    channel_id: Option<String>,
+    guild_id: Option<String>,
+    member: Option<Member>,
+    user: Option<User>,
+    locale: Option<String>,
+    data: Option<InteractionData>,
+}
+
+#[derive(Deserialize)]
+struct Member {
+    user: User,
+    permissions: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct User {
+    id: String,
+}
+
+#[derive(Deserialize)]
+struct InteractionData {
+    name: Option<String>,
+    #[serde(default)]
+    options: Vec<CommandOption>,
+    custom_id: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct CommandOption {
+    name: String,
+    value: Value,
+}
+
+#[derive(Serialize)]
+struct CommandDefinition {
+    name: &'static str,
+    description: &'static str,
+    options: Vec<OptionDefinition>,
+}
+
+#[derive(Serialize)]
+struct OptionDefinition {
+    #[serde(rename = "type")]
+    kind: u8,
+    name: &'static str,
+    description: &'static str,
+    required: bool,
+}
+
+impl InteractionData {
+    /// Arguments in the order the text commands take them, whatever order the options were
+    /// filled in: the tracking number, then the courier id, then the value to set.
+    fn args(&self) -> String {
+        let name = self.name.as_deref().unwrap_or_default();
+        ["tracking_number", "courier_id", name]
+            .iter()
+            .filter_map(|option_name| {
+                let option = self
+                    .options
+                    .iter()
+                    .find(|option| option.name == *option_name)?;
+                match &option.value {
+                    Value::String(value) => Some(value.clone()),
+                    Value::Null => None,
+                    value => Some(value.to_string()),
+                }
+            })
+            .collect::<Vec<String>>()
+            .join(" ")
+    }
+}
+
+impl Interaction {
+    fn session(&self) -> Option<Session> {
+        let (user, is_chat_admin) = match (&self.member, &self.user) {
+            (Some(member), _) => {
+                let permissions = member
+                    .permissions
+                    .as_deref()
+                    .and_then(|permissions| permissions.parse::<u64>().ok())
+                    .unwrap_or_default();
+                (&member.user, permissions & MANAGE_MESSAGES != 0)
+            }
+            (None, Some(user)) => (user, true),
+            (None, None) => return None,
+        };
+        let channel_id = self.channel_id.as_ref().or(self.guild_id.as_ref())?;
+        Some(Session {
+            user_id: tracker_user_id(channel_id),
+            requested_by: user.id.clone(),
+            is_chat_admin,
+            settings: UserSettings {
+                language_code: self.locale.clone(),
+                ..UserSettings::default()
+            },
+            dialogue: false,
+        })
+    }
+}
+
+pub fn tracker_user_id(channel_id: &str) -> String {
+    format!("discord:{}", channel_id)
+}
+
+fn verify_signature(public_key: &str, request: &HttpRequest, body: &[u8]) -> bool {
+    let header = |name: &str| {
+        request
+            .headers()
+            .get(name)
+            .and_then(|value| value.to_str().ok())
+    };
+    let (signature, timestamp) = match (header(SIGNATURE_HEADER), header(TIMESTAMP_HEADER)) {
+        (Some(signature), Some(timestamp)) => (signature, timestamp),
+        _ => return false,
+    };
+    let public_key = match hex::decode(public_key)
+        .ok()
+        .and_then(|bytes| PublicKey::from_bytes(&bytes).ok())
+    {
+        Some(public_key) => public_key,
+        None => return false,
+    };
+    let signature = match hex::decode(signature)
+        .ok()
+        .and_then(|bytes| Signature::try_from(bytes.as_slice()).ok())
+    {
+        Some(signature) => signature,
+        None => return false,
+    };
+
+    let mut message = timestamp.as_bytes().to_vec();
+    message.extend_from_slice(body);
+    public_key.verify(&message, &signature).is_ok()
+}
+
+fn components(buttons: Vec<Vec<Button>>) -> Vec<Value> {
+    buttons
+        .into_iter()
+        .flat_map(|row| {
+            row.chunks(MAX_BUTTONS_PER_ROW)
+                .map(|row| row.to_vec())
+                .collect::<Vec<_>>()
+        })
+        .take(MAX_ROWS)
+        .map(|row| {
+            let buttons: Vec<Value> = row
+                .into_iter()
+                .map(|button| {
+                    let label: String = button.label.chars().take(80).collect();
+                    match button.action {
+                        ButtonAction::Callback(custom_id) => {
+                            json!({ "type": 2, "style": 1, "label": label, "custom_id": custom_id })
+                        }
+                        ButtonAction::Url(url) => {
+                            json!({ "type": 2, "style": 5, "label": label, "url": url })
+                        }
+                    }
+                })
+                .collect();
+            json!({ "type": 1, "components": buttons })
+        })
+        .collect()
+}
+
+fn message(reply: Reply) -> Value {
+    let content: String = reply.text.chars().take(MAX_CONTENT_LENGTH).collect();
+    json!({
+        "content": content,
+        "components": components(reply.buttons),
+    })
+}
+
+/// Replaces the deferred response with the rendered reply.
+async fn edit_original(application_id: &str, token: &str, reply: Reply) {
+    let url = format!(
+        "{}/webhooks/{}/{}/messages/@original",
+        API_URL, application_id, token
+    );
+    match CLIENT.patch(&url).json(&message(reply)).send().await {
+        Ok(response) if response.status().is_success() => {}
+        Ok(response) => warn!(status = %response.status(), "Failed to edit Discord response"),
+        Err(err) => warn!(error = %err, "Failed to edit Discord response"),
+    }
+}
+
+pub async fn interactions(
+    app: web::Data<AppState>,
+    request: HttpRequest,
+    body: web::Bytes,
+) -> HttpResponse {
+    let public_key = match &app.config.discord.public_key {
+        Some(public_key) => public_key,
+        None => return HttpResponse::NotFound().finish(),
+    };
+    if !verify_signature(public_key, &request, &body) {
+        metrics::UPDATES
+            .with_label_values(&["discord_unauthorized"])
+            .inc();
+        return HttpResponse::Unauthorized().finish();
+    }
+    let interaction: Interaction = match serde_json::from_slice(&body) {
+        Ok(interaction) => interaction,
+        Err(_) => return HttpResponse::BadRequest().finish(),
+    };
+
+    match interaction.kind {
+        // PING
+        1 => HttpResponse::Ok().json(json!({ "type": 1 })),
+        // APPLICATION_COMMAND
+        2 => {
+            metrics::UPDATES
+                .with_label_values(&["discord_command"])
+                .inc();
+            let session = match interaction.session() {
+                Some(session) => session,
+                None => return HttpResponse::BadRequest().finish(),
+            };
+            let data = interaction.data.unwrap_or(InteractionData {
+                name: None,
+                options: Vec::new(),
+                custom_id: None,
+            });
+            let args = data.args();
+            let name = data.name.unwrap_or_default();
+            let (application_id, token) = (interaction.application_id, interaction.token);
+            actix_web::rt::spawn(async move {
+                let reply = conversation::handle_command::<DiscordMarkdown>(
+                    &app,
+                    &session,
+                    &name,
+                    Args::new(&args),
+                )
+                .await
+                .unwrap_or_else(|| conversation::help::<DiscordMarkdown>(&session));
+                edit_original(&application_id, &token, reply).await;
+            });
+            // DEFERRED_CHANNEL_MESSAGE_WITH_SOURCE
+            HttpResponse::Ok().json(json!({ "type": 5 }))
+        }
+        // MESSAGE_COMPONENT
+        3 => {
+            metrics::UPDATES
+                .with_label_values(&["discord_component"])
+                .inc();
+            let session = match interaction.session() {
+                Some(session) => session,
+                None => return HttpResponse::BadRequest().finish(),
+            };
+            let custom_id = match interaction.data.and_then(|data| data.custom_id) {
+                Some(custom_id) => custom_id,
+                None => return HttpResponse::BadRequest().finish(),
+            };
+            let (application_id, token) = (interaction.application_id, interaction.token);
+            actix_web::rt::spawn(async move {
+                if let Some(reply) =
+                    conversation::handle_action::<DiscordMarkdown>(&app, &session, &custom_id).await
+                {
+                    edit_original(&application_id, &token, reply).await;
+                }
+            });
+            // DEFERRED_UPDATE_MESSAGE
+            HttpResponse::Ok().json(json!({ "type": 6 }))
+        }
+        _ => HttpResponse::BadRequest().finish(),
+    }
+}
+
+fn command_definitions() -> Vec<CommandDefinition> {
+    let options = |description| {
+        vec![
+            OptionDefinition {
+                kind: 3,
+                name: "tracking_number",
+                description,
+                required: true,
+            },
+            OptionDefinition {
+                kind: 3,
+                name: "courier_id",
+                description: "Courier id, e.g. kr.cjlogistics",
+                required: false,
+            },
+        ]
+    };
+    let update_options = |name, description| {
+        let mut options = options("Tracked number to update");
+        options.push(OptionDefinition {
+            kind: 3,
+            name,
+            description,
+            required: false,
+        });
+        options
+    };
+    vec![
+        CommandDefinition {
+            name: "help",
+            description: "Show how to use the bot",
+            options: Vec::new(),
+        },
+        CommandDefinition {
+            name: "search",
+            description: "Look up a parcel",
+            options: options("Tracking number to look up"),
+        },
+        CommandDefinition {
+            name: "track",
+            description: "Track a parcel in this channel",
+            options: options("Tracking number to track"),
+        },
+        CommandDefinition {
+            name: "list",
+            description: "List parcels tracked in this channel",
+            options: Vec::new(),
+        },
+        CommandDefinition {
+            name: "label",
+            description: "Name a tracked parcel",
+            options: update_options("label", "New label, leave empty to clear"),
+        },
+        CommandDefinition {
+            name: "note",
+            description: "Add a note to a tracked parcel",
+            options: update_options("note", "New note, leave empty to clear"),
+        },
+        CommandDefinition {
+            name: "tags",
+            description: "Tag a tracked parcel",
+            options: update_options("tags", "Space separated tags, leave empty to clear"),
+        },
+    ]
+}
+
+/// Overwrites the application's global slash commands.
+pub async fn register_commands(config: &DiscordConfig) {
+    let (application_id, bot_token) = match (&config.application_id, &config.bot_token) {
+        (Some(application_id), Some(bot_token)) => (application_id, bot_token),
+        _ => return,
+    };
+    let url = format!("{}/applications/{}/commands", API_URL, application_id);
+    let result = CLIENT
+        .put(&url)
+        .header(reqwest::header::AUTHORIZATION, format!("Bot {}", bot_token))
+        .json(&command_definitions())
+        .send()
+        .await;
+    match result {
+        Ok(response) if response.status().is_success() => {
+            info!("Registered Discord slash commands")
+        }
+        Ok(response) => warn!(status = %response.status(), "Failed to register Discord commands"),
+        Err(err) => warn!(error = %err, "Failed to register Discord commands"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use actix_web::test::TestRequest;
+    use ed25519_dalek::{Keypair, SecretKey, Signer};
+
+    use super::*;
+
+    const BODY: &[u8] = br#"{"type":1}"#;
+    const TIMESTAMP: &str = "1700000000";
+
+    fn keypair() -> Keypair {
+        let secret = SecretKey::from_bytes(&[7; 32]).unwrap();
+        let public = PublicKey::from(&secret);
+        Keypair { secret, public }
+    }
+
+    fn signed_request(keypair: &Keypair, body: &[u8]) -> HttpRequest {
+        let mut message = TIMESTAMP.as_bytes().to_vec();
+        message.extend_from_slice(body);
+        let signature = hex::encode(keypair.sign(&message).to_bytes());
+        TestRequest::default()
+            .insert_header((SIGNATURE_HEADER, signature))
+            .insert_header((TIMESTAMP_HEADER, TIMESTAMP))
+            .to_http_request()
+    }
+
+    #[test]
+    fn accepts_signed_requests() {
+        let keypair = keypair();
+        let public_key = hex::encode(keypair.public.as_bytes());
+        assert!(verify_signature(
+            &public_key,
+            &signed_request(&keypair, BODY),
+            BODY
+        ));
+    }
+
+    #[test]
+    fn orders_options_by_name() {
+        let data: InteractionData = serde_json::from_value(json!({
+            "name": "label",
+            "options": [
+                { "name": "label", "type": 3, "value": "Winter coat" },
+                { "name": "courier_id", "type": 3, "value": "kr.cjlogistics" },
+                { "name": "tracking_number", "type": 3, "value": "123456789012" },
+            ],
+        }))
+        .unwrap();
+        assert_eq!(data.args(), "123456789012 kr.cjlogistics Winter coat");
+
+        let data: InteractionData = serde_json::from_value(json!({
+            "name": "search",
+            "options": [{ "name": "tracking_number", "type": 3, "value": "123456789012" }],
+        }))
+        .unwrap();
+        assert_eq!(data.args(), "123456789012");
+    }
+
+    #[test]
+    fn registers_every_shared_command() {
+        let names: Vec<&str> = command_definitions()
+            .iter()
+            .map(|definition| definition.name)
+            .collect();
+        for name in ["help", "search", "track", "list", "label", "note", "tags"] {
+            assert!(names.contains(&name), "{}", name);
+        }
+    }
+
+    #[test]
+    fn rejects_tampered_or_unsigned_requests() {
+        let keypair = keypair();
+        let public_key = hex::encode(keypair.public.as_bytes());
+        let request = signed_request(&keypair, BODY);
+        assert!(!verify_signature(&public_key, &request, br#"{"type":2}"#));
+        assert!(!verify_signature(&"00".repeat(32), &request, BODY));
+        assert!(!verify_signature(
+            &public_key,
+            &TestRequest::default().to_http_request(),
+            BODY
+        ));
+    }
+}
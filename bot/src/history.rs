@@ -0,0 +1,122 @@
+use std::{
+    collections::HashMap,
+    sync::Mutex,
+    time::{Duration, Instant},
+};
+
+use bot::tracker::TrackingInfo;
+use once_cell::sync::Lazy;
+
+const HISTORY_TTL: Duration = Duration::from_secs(10 * 60);
+const HISTORY_CAPACITY: usize = 256;
+
+static GLOBAL_DATA: Lazy<Mutex<HistoryCache>> =
+    Lazy::new(|| Mutex::new(HistoryCache::new(HISTORY_TTL, HISTORY_CAPACITY)));
+
+/// Tracking info fetched for "show all", so turning its pages doesn't scrape the courier again.
+struct HistoryCache {
+    ttl: Duration,
+    capacity: usize,
+    inserted: u64,
+    entries: HashMap<(String, String), Entry>,
+}
+
+struct Entry {
+    order: u64,
+    cached_at: Instant,
+    info: TrackingInfo,
+}
+
+impl HistoryCache {
+    fn new(ttl: Duration, capacity: usize) -> Self {
+        Self {
+            ttl,
+            capacity,
+            inserted: 0,
+            entries: HashMap::new(),
+        }
+    }
+
+    fn get(&self, courier_id: &str, tracking_number: &str) -> Option<TrackingInfo> {
+        self.entries
+            .get(&(courier_id.to_string(), tracking_number.to_string()))
+            .filter(|entry| entry.cached_at.elapsed() < self.ttl)
+            .map(|entry| entry.info.clone())
+    }
+
+    fn insert(&mut self, courier_id: &str, tracking_number: &str, info: TrackingInfo) {
+        let ttl = self.ttl;
+        self.entries
+            .retain(|_, entry| entry.cached_at.elapsed() < ttl);
+        if self.entries.len() >= self.capacity {
+            let oldest = self
+                .entries
+                .iter()
+                .min_by_key(|(_, entry)| entry.order)
+                .map(|(key, _)| key.clone());
+            if let Some(oldest) = oldest {
+                self.entries.remove(&oldest);
+            }
+        }
+        self.inserted += 1;
+        self.entries.insert(
+            (courier_id.to_string(), tracking_number.to_string()),
+            Entry {
+                order: self.inserted,
+                cached_at: Instant::now(),
+                info,
+            },
+        );
+    }
+}
+
+pub fn get(courier_id: &str, tracking_number: &str) -> Option<TrackingInfo> {
+    GLOBAL_DATA
+        .lock()
+        .ok()
+        .and_then(|cache| cache.get(courier_id, tracking_number))
+}
+
+pub fn insert(courier_id: &str, tracking_number: &str, info: TrackingInfo) {
+    if let Ok(mut cache) = GLOBAL_DATA.lock() {
+        cache.insert(courier_id, tracking_number, info);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn info(tracking_number: &str) -> TrackingInfo {
+        TrackingInfo {
+            tracking_number: tracking_number.to_string(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn returns_cached_info() {
+        let mut cache = HistoryCache::new(HISTORY_TTL, HISTORY_CAPACITY);
+        cache.insert("cj", "1234", info("1234"));
+        assert_eq!(cache.get("cj", "1234"), Some(info("1234")));
+        assert_eq!(cache.get("hanjin", "1234"), None);
+    }
+
+    #[test]
+    fn expires_entries() {
+        let mut cache = HistoryCache::new(Duration::ZERO, HISTORY_CAPACITY);
+        cache.insert("cj", "1234", info("1234"));
+        assert_eq!(cache.get("cj", "1234"), None);
+    }
+
+    #[test]
+    fn evicts_the_oldest_entry() {
+        let mut cache = HistoryCache::new(HISTORY_TTL, 2);
+        cache.insert("cj", "1", info("1"));
+        cache.insert("cj", "2", info("2"));
+        cache.insert("cj", "3", info("3"));
+        assert_eq!(cache.get("cj", "1"), None);
+        assert_eq!(cache.get("cj", "2"), Some(info("2")));
+        assert_eq!(cache.get("cj", "3"), Some(info("3")));
+    }
+}
@@ -0,0 +1,141 @@
+use std::{collections::HashMap, sync::Mutex, time::Duration};
+
+use bot::tracker::{ExtractTrackingNumbersRequest, SearchRequest, SearchResponse, StatusKind};
+use futures::future::join_all;
+use once_cell::sync::Lazy;
+use telbot_hyper::types::{
+    markup::ParseMode,
+    query::{
+        AnswerInlineQuery, InlineQuery, InlineQueryResult, InlineQueryResultKind,
+        InputMessageContent,
+    },
+};
+use tracing::warn;
+
+use crate::{
+    settings::UserSettings,
+    state::AppState,
+    telegram::{create_search_result_keyboard, create_simple_tracking_message},
+};
+
+/// Shortest query worth looking up, the extractor ignores anything shorter.
+const MIN_QUERY_LENGTH: usize = 8;
+/// How long a user has to stop typing before their query is searched.
+const DEBOUNCE: Duration = Duration::from_millis(500);
+
+/// Latest inline query id of each user, older queries are dropped while they type.
+static GLOBAL_DATA: Lazy<Mutex<HashMap<i64, String>>> = Lazy::new(|| Mutex::new(HashMap::new()));
+
+pub async fn handle_inline_query(app: &AppState, inline_query: &InlineQuery) {
+    let query = inline_query.query.trim();
+    let settings = app.settings.get(inline_query.from.id);
+    let results = if query.len() < MIN_QUERY_LENGTH {
+        Vec::new()
+    } else {
+        if !settle(inline_query).await {
+            return;
+        }
+        search_candidates(app, query, &settings).await
+    };
+
+    let mut answer = AnswerInlineQuery::new(inline_query.id.clone(), results).with_cache_time(60);
+    answer.is_personal = Some(true);
+    if let Err(err) = app.api.send_json(&answer).await {
+        warn!("Failed to answer inline query: {:?}", err);
+    }
+}
+
+/// Waits out the debounce, returning whether `inline_query` is still the user's latest one.
+async fn settle(inline_query: &InlineQuery) -> bool {
+    let user_id = inline_query.from.id;
+    if let Ok(mut latest) = GLOBAL_DATA.lock() {
+        latest.insert(user_id, inline_query.id.clone());
+    }
+    tokio::time::sleep(DEBOUNCE).await;
+
+    match GLOBAL_DATA.lock() {
+        Ok(mut latest) if latest.get(&user_id) == Some(&inline_query.id) => {
+            latest.remove(&user_id);
+            true
+        }
+        Ok(_) => false,
+        Err(_) => true,
+    }
+}
+
+/// Searches the tracking numbers the extractor accepts in `query`, only with their couriers.
+async fn search_candidates(
+    app: &AppState,
+    query: &str,
+    settings: &UserSettings,
+) -> Vec<InlineQueryResult> {
+    let mut client = app.tracker();
+
+    let request = tonic::Request::new(ExtractTrackingNumbersRequest {
+        text: query.to_string(),
+    });
+    let candidates = match client.extract_tracking_numbers(request).await {
+        Ok(response) => response.into_inner().candidates,
+        Err(_) => return Vec::new(),
+    };
+
+    let searches = candidates.into_iter().flat_map(|candidate| {
+        let client = client.clone();
+        let tracking_number = candidate.tracking_number;
+        candidate.couriers.into_iter().map(move |courier| {
+            let mut client = client.clone();
+            let request = tonic::Request::new(SearchRequest {
+                courier_id: courier.id,
+                tracking_number: tracking_number.clone(),
+            });
+            async move { client.search(request).await.ok() }
+        })
+    });
+
+    join_all(searches)
+        .await
+        .into_iter()
+        .flatten()
+        .map(|response| response.into_inner())
+        .filter(|response| response.status() == StatusKind::Ok)
+        .filter_map(|response| create_inline_result(&response, settings))
+        .collect()
+}
+
+fn create_inline_result(
+    response: &SearchResponse,
+    settings: &UserSettings,
+) -> Option<InlineQueryResult> {
+    let info = response.tracking_info.as_ref()?;
+    let description = info.tracks.last().and_then(|detail| {
+        detail
+            .status
+            .as_ref()
+            .or_else(|| detail.message.as_ref())
+            .cloned()
+    });
+
+    Some(InlineQueryResult {
+        id: info.id.clone(),
+        kind: InlineQueryResultKind::Article {
+            title: format!("{} {}", info.name, info.tracking_number),
+            input_message_content: InputMessageContent::Text {
+                message_text: create_simple_tracking_message(response, settings),
+                parse_mode: Some(ParseMode::MarkdownV2),
+                entities: None,
+                disable_web_page_preview: None,
+            },
+            url: None,
+            hide_url: None,
+            description,
+            thumb_url: None,
+            thumb_width: None,
+            thumb_height: None,
+        },
+        reply_markup: Some(create_search_result_keyboard(
+            info,
+            false,
+            settings.locale(),
+        )),
+    })
+}
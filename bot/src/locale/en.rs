@@ -0,0 +1,98 @@
+use super::Text;
+
+pub fn text(text: Text) -> &'static str {
+    match text {
+        Text::Help => {
+            "/help - Show this help.\n\
+            /search - Look up a parcel by its tracking number.\n\
+            /track - Start tracking a parcel by its tracking number.\n\
+            /list - Manage the parcels you are tracking.\n\
+            /label, /note, /tags - Add a name, note or tags to a tracked parcel.\n\
+            /language - Change the language.\n\
+            /timezone - Change the time zone.\n\
+            /cancel - Cancel the current conversation."
+        }
+        Text::Cancelled => "❌ Cancelled.",
+        Text::EnterSearchTrackingNumber => "💬 Please enter the tracking number to look up.",
+        Text::EnterTrackTrackingNumber => "💬 Please enter the tracking number to start tracking.",
+        Text::NoSupportedCourier => {
+            "⚠️ No supported courier was found.\n\
+            Please check the tracking number or contact the administrator."
+        }
+        Text::SelectSearchCourier => "🚚 Please choose the courier to look up.",
+        Text::SelectTrackCourier => "🚚 Please choose the courier to track.",
+        Text::SelectTrackingNumber => "🔢 Found several tracking numbers. Please choose one.",
+        Text::ServerError => "⚠️ Something went wrong on the server. Please try again later.",
+        Text::NoTrackingInfo => "⚠️ No tracking information found.",
+        Text::RequestFailed => {
+            "⚠️ Couldn't fetch information from the courier.\n\
+            The courier's website may be having trouble, please try again later."
+        }
+        Text::WrongTrackingNumber => "⚠️ The tracking number doesn't match this courier's format.",
+        Text::ExpectedFormat => "Expected format:",
+        Text::NotExistsTrackingNumber => {
+            "⚠️ No tracking information found.\n\
+            The parcel may not be registered yet, or it may belong to another courier."
+        }
+        Text::RetryButton => "🔄 Retry",
+        Text::ChooseAnotherCourierButton => "🚚 Choose another courier",
+        Text::TrackingStarted => "✅ Started tracking the parcel.",
+        Text::TrackingAlreadyExists => "⚠️ This parcel is already being tracked.",
+        Text::TrackingListEmpty => "📭 You are not tracking any parcels.",
+        Text::TrackingDetailUnavailable => "⚠️ Couldn't load the tracking details.",
+        Text::StopTrackingButton => "Stop tracking",
+        Text::TrackingStopped => "🗑 Stopped tracking the parcel.",
+        Text::NotTracking => "⚠️ This parcel is not being tracked.",
+        Text::TrackUpdated => "✏️ Updated the parcel.",
+        Text::TrackUpdateUsage => {
+            "✏️ Please enter a tracking number followed by the value. Leave it empty to clear.\n\
+            e.g. /label 123456789012 Winter coat, /note 123456789012 Left at the front desk, /tags 123456789012 gift clothes"
+        }
+        Text::TrackUpdateCourierRequired => {
+            "✏️ Several couriers track this number. Please enter the courier id after the tracking number.\n\
+            e.g. /label 123456789012 kr.cjlogistics Winter coat"
+        }
+        Text::StopTrackingPermissionDenied => {
+            "⚠️ Only the user who added this parcel or an admin can stop tracking it."
+        }
+        Text::NoInformation => "Unknown",
+        Text::ShowAllButton => "📄 Show all events",
+        Text::TrackButton => "🔍 Track parcel",
+        Text::LiveTrackingButton => "🚚 Live delivery location",
+        Text::CourierContact => "📞 Delivery driver:",
+        Text::OpenHomepageButton => "🔗 Open on the website",
+        Text::OlderButton => "◀️ Older",
+        Text::NewerButton => "Newer ▶️",
+        Text::LanguageUsage => {
+            "🌐 Please enter a language.\n\
+            e.g. /language ko, /language en, /language ja, /language auto"
+        }
+        Text::LanguageChanged => "🌐 Language changed to English.",
+        Text::TimezoneUsage => {
+            "🕒 Please enter a time zone.\n\
+            e.g. /timezone Asia/Seoul, /timezone America/New_York\n\
+            Use /timezone original on to also show the courier's local time.\n\
+            Current time zone:"
+        }
+        Text::TimezoneChanged => "🕒 Time zone changed to",
+        Text::InvalidTimezone => "⚠️ Unknown time zone. Please enter an IANA time zone name.",
+        Text::OriginalTimeShown => "🕒 The courier's local time will also be shown.",
+        Text::OriginalTimeHidden => "🕒 The courier's local time will be hidden.",
+        Text::StatsUsers => "👥 Users:",
+        Text::StatsTrackingChats => "💬 Chats tracking parcels:",
+        Text::StatsSubscriptions => "📦 Tracked parcels:",
+        Text::CourierHealthEmpty => "📊 No lookups recorded yet.",
+        Text::BroadcastUsage => {
+            "📢 Please enter the message to send.\n\
+            e.g. /broadcast Scheduled maintenance notice"
+        }
+        Text::BroadcastStarted => "📢 Sending the broadcast, the result follows when done.",
+        Text::BroadcastSent => "📢 Broadcast sent.",
+        Text::BanUsage => {
+            "🚫 Please enter the user id to ban. Admins can't be banned.\n\
+            e.g. /ban 123456789, /unban 123456789"
+        }
+        Text::UserBanned => "🚫 Banned user",
+        Text::UserUnbanned => "✅ Unbanned user",
+    }
+}
@@ -0,0 +1,104 @@
+use super::Text;
+
+pub fn text(text: Text) -> &'static str {
+    match text {
+        Text::Help => {
+            "/help - ヘルプを表示します。\n\
+            /search - 追跡番号で荷物を照会します。\n\
+            /track - 追跡番号で荷物の追跡を開始します。\n\
+            /list - 追跡中の荷物を管理します。\n\
+            /label, /note, /tags - 追跡中の荷物に名前、メモ、タグを付けます。\n\
+            /language - 言語を変更します。\n\
+            /timezone - タイムゾーンを変更します。\n\
+            /cancel - 会話をキャンセルします。"
+        }
+        Text::Cancelled => "❌ キャンセルしました。",
+        Text::EnterSearchTrackingNumber => "💬 照会する追跡番号を入力してください。",
+        Text::EnterTrackTrackingNumber => "💬 追跡を開始する追跡番号を入力してください。",
+        Text::NoSupportedCourier => {
+            "⚠️ 対応している配送業者がありません。\n\
+            追跡番号をもう一度確認するか、管理者にお問い合わせください。"
+        }
+        Text::SelectSearchCourier => "🚚 照会する配送業者を選択してください。",
+        Text::SelectTrackCourier => "🚚 追跡する配送業者を選択してください。",
+        Text::SelectTrackingNumber => {
+            "🔢 複数の追跡番号が見つかりました。使用する番号を選択してください。"
+        }
+        Text::ServerError => {
+            "⚠️ サーバーに問題が発生しました。しばらくしてから再度お試しください。"
+        }
+        Text::NoTrackingInfo => "⚠️ 追跡情報がありません。",
+        Text::RequestFailed => {
+            "⚠️ 配送業者から情報を取得できませんでした。\n\
+            配送業者のサイトに問題がある可能性があります。しばらくしてから再度お試しください。"
+        }
+        Text::WrongTrackingNumber => "⚠️ この配送業者の追跡番号の形式と一致しません。",
+        Text::ExpectedFormat => "正しい形式:",
+        Text::NotExistsTrackingNumber => {
+            "⚠️ 追跡情報がありません。\n\
+            まだ配送業者に登録されていないか、別の配送業者の追跡番号の可能性があります。"
+        }
+        Text::RetryButton => "🔄 再試行",
+        Text::ChooseAnotherCourierButton => "🚚 別の配送業者を選択",
+        Text::TrackingStarted => "✅ 荷物の追跡を開始しました。",
+        Text::TrackingAlreadyExists => "⚠️ すでに追跡中の荷物です。",
+        Text::TrackingListEmpty => "📭 追跡中の荷物はありません。",
+        Text::TrackingDetailUnavailable => "⚠️ 追跡情報を読み込めませんでした。",
+        Text::StopTrackingButton => "追跡を停止",
+        Text::TrackingStopped => "🗑 荷物の追跡を停止しました。",
+        Text::NotTracking => "⚠️ 追跡中の荷物ではありません。",
+        Text::TrackUpdated => "✏️ 荷物の情報を更新しました。",
+        Text::TrackUpdateUsage => {
+            "✏️ 追跡番号と内容を入力してください。内容を空にすると削除されます。\n\
+            例) /label 123456789012 冬のコート, /note 123456789012 管理室で保管, /tags 123456789012 ギフト 衣類"
+        }
+        Text::TrackUpdateCourierRequired => {
+            "✏️ 複数の配送業者で追跡中の番号です。追跡番号の後に配送業者IDを入力してください。\n\
+            例) /label 123456789012 kr.cjlogistics 冬のコート"
+        }
+        Text::StopTrackingPermissionDenied => {
+            "⚠️ 荷物を追加したユーザーか管理者のみ追跡を停止できます。"
+        }
+        Text::NoInformation => "情報なし",
+        Text::ShowAllButton => "📄 すべての履歴を表示",
+        Text::TrackButton => "🔍 荷物を追跡",
+        Text::LiveTrackingButton => "🚚 リアルタイム配達位置",
+        Text::CourierContact => "📞 配達担当:",
+        Text::OpenHomepageButton => "🔗 公式サイトで見る",
+        Text::OlderButton => "◀️ 以前の履歴",
+        Text::NewerButton => "最近の履歴 ▶️",
+        Text::LanguageUsage => {
+            "🌐 使用する言語を入力してください。\n\
+            例) /language ko, /language en, /language ja, /language auto"
+        }
+        Text::LanguageChanged => "🌐 言語を日本語に変更しました。",
+        Text::TimezoneUsage => {
+            "🕒 使用するタイムゾーンを入力してください。\n\
+            例) /timezone Asia/Seoul, /timezone America/New_York\n\
+            /timezone original on で配送業者の現地時間も表示できます。\n\
+            現在のタイムゾーン:"
+        }
+        Text::TimezoneChanged => "🕒 タイムゾーンを変更しました:",
+        Text::InvalidTimezone => {
+            "⚠️ 不明なタイムゾーンです。IANAタイムゾーン名を入力してください。"
+        }
+        Text::OriginalTimeShown => "🕒 配送業者の現地時間も表示します。",
+        Text::OriginalTimeHidden => "🕒 配送業者の現地時間を表示しません。",
+        Text::StatsUsers => "👥 ユーザー:",
+        Text::StatsTrackingChats => "💬 追跡中のチャット:",
+        Text::StatsSubscriptions => "📦 追跡中の荷物:",
+        Text::CourierHealthEmpty => "📊 まだ照会記録がありません。",
+        Text::BroadcastUsage => {
+            "📢 送信するメッセージを入力してください。\n\
+            例) /broadcast メンテナンスのお知らせ"
+        }
+        Text::BroadcastStarted => "📢 お知らせを送信しています。完了したら結果をお知らせします。",
+        Text::BroadcastSent => "📢 お知らせを送信しました。",
+        Text::BanUsage => {
+            "🚫 ブロックするユーザーIDを入力してください。管理者はブロックできません。\n\
+            例) /ban 123456789, /unban 123456789"
+        }
+        Text::UserBanned => "🚫 ユーザーをブロックしました:",
+        Text::UserUnbanned => "✅ ユーザーのブロックを解除しました:",
+    }
+}
@@ -0,0 +1,100 @@
+use super::Text;
+
+pub fn text(text: Text) -> &'static str {
+    match text {
+        Text::Help => {
+            "/help - 도움말을 볼 수 있어요.\n\
+            /search - 운송장 번호로 택배를 조회할 수 있어요.\n\
+            /track - 운송장 번호로 택배를 추적할 수 있어요.\n\
+            /list - 현재 추적중인 운송장을 관리할 수 있어요.\n\
+            /label, /note, /tags - 추적중인 운송장에 이름, 메모, 태그를 붙일 수 있어요.\n\
+            /language - 언어를 변경할 수 있어요.\n\
+            /timezone - 시간대를 변경할 수 있어요.\n\
+            /cancel - 대화를 취소할 수 있어요."
+        }
+        Text::Cancelled => "❌ 취소되었어요.",
+        Text::EnterSearchTrackingNumber => "💬 조회할 운송장 번호를 입력해 주세요.",
+        Text::EnterTrackTrackingNumber => "💬 추적을 시작할 운송장 번호를 입력해 주세요.",
+        Text::NoSupportedCourier => {
+            "⚠️ 지원하는 택배사가 없어요.\n\
+            운송장 번호를 다시 확인하시거나 관리자에게 문의해 주세요."
+        }
+        Text::SelectSearchCourier => "🚚 운송장을 조회할 택배사를 선택해 주세요.",
+        Text::SelectTrackCourier => "🚚 운송장을 추적할 택배사를 선택해 주세요.",
+        Text::SelectTrackingNumber => {
+            "🔢 여러 운송장 번호를 찾았어요. 사용할 번호를 선택해 주세요."
+        }
+        Text::ServerError => "⚠️ 서버에 문제가 있어요. 나중에 다시 시도해 주세요.",
+        Text::NoTrackingInfo => "⚠️ 운송장 정보가 없어요.",
+        Text::RequestFailed => {
+            "⚠️ 택배사에서 정보를 가져오지 못했어요.\n\
+            택배사 홈페이지에 문제가 있을 수 있으니 잠시 후 다시 시도해 주세요."
+        }
+        Text::WrongTrackingNumber => "⚠️ 이 택배사의 운송장 번호 형식과 맞지 않아요.",
+        Text::ExpectedFormat => "올바른 형식:",
+        Text::NotExistsTrackingNumber => {
+            "⚠️ 운송장 정보가 없어요.\n\
+            아직 택배사에 등록되지 않았거나 다른 택배사의 운송장일 수 있어요."
+        }
+        Text::RetryButton => "🔄 다시 시도",
+        Text::ChooseAnotherCourierButton => "🚚 다른 택배사 선택",
+        Text::TrackingStarted => "✅ 운송장 추적을 시작했어요.",
+        Text::TrackingAlreadyExists => "⚠️ 이미 추적중인 운송장이에요.",
+        Text::TrackingListEmpty => "📭 추적중인 운송장이 없어요.",
+        Text::TrackingDetailUnavailable => "⚠️ 처리 정보를 불러오지 못했어요.",
+        Text::StopTrackingButton => "추적 중지",
+        Text::TrackingStopped => "🗑 운송장 추적을 중지했어요.",
+        Text::NotTracking => "⚠️ 추적중인 운송장이 아니에요.",
+        Text::TrackUpdated => "✏️ 운송장 정보를 수정했어요.",
+        Text::TrackUpdateUsage => {
+            "✏️ 운송장 번호와 함께 입력해 주세요. 내용을 비우면 지워져요.\n\
+            예) /label 123456789012 겨울 코트, /note 123456789012 경비실 보관, /tags 123456789012 선물 의류"
+        }
+        Text::TrackUpdateCourierRequired => {
+            "✏️ 여러 택배사로 추적중인 운송장이에요. 운송장 번호 뒤에 택배사 ID를 입력해 주세요.\n\
+            예) /label 123456789012 kr.cjlogistics 겨울 코트"
+        }
+        Text::StopTrackingPermissionDenied => {
+            "⚠️ 운송장을 추가한 사용자나 관리자만 추적을 중지할 수 있어요."
+        }
+        Text::NoInformation => "정보 없음",
+        Text::ShowAllButton => "📄 모든 처리 정보 표시",
+        Text::TrackButton => "🔍 운송장 추적",
+        Text::LiveTrackingButton => "🚚 실시간 배송 위치",
+        Text::CourierContact => "📞 배송 담당:",
+        Text::OpenHomepageButton => "🔗 홈페이지에서 보기",
+        Text::OlderButton => "◀️ 이전 기록",
+        Text::NewerButton => "최근 기록 ▶️",
+        Text::LanguageUsage => {
+            "🌐 사용할 언어를 입력해 주세요.\n\
+            예) /language ko, /language en, /language ja, /language auto"
+        }
+        Text::LanguageChanged => "🌐 언어를 한국어로 변경했어요.",
+        Text::TimezoneUsage => {
+            "🕒 사용할 시간대를 입력해 주세요.\n\
+            예) /timezone Asia/Seoul, /timezone America/New_York\n\
+            /timezone original on 으로 택배사 현지 시간도 함께 볼 수 있어요.\n\
+            현재 시간대:"
+        }
+        Text::TimezoneChanged => "🕒 시간대를 변경했어요:",
+        Text::InvalidTimezone => "⚠️ 알 수 없는 시간대예요. IANA 시간대 이름을 입력해 주세요.",
+        Text::OriginalTimeShown => "🕒 택배사 현지 시간을 함께 표시할게요.",
+        Text::OriginalTimeHidden => "🕒 택배사 현지 시간을 표시하지 않을게요.",
+        Text::StatsUsers => "👥 사용자:",
+        Text::StatsTrackingChats => "💬 추적중인 대화방:",
+        Text::StatsSubscriptions => "📦 추적중인 운송장:",
+        Text::CourierHealthEmpty => "📊 아직 조회 기록이 없어요.",
+        Text::BroadcastUsage => {
+            "📢 보낼 메시지를 입력해 주세요.\n\
+            예) /broadcast 서버 점검 안내"
+        }
+        Text::BroadcastStarted => "📢 공지를 보내고 있어요. 끝나면 결과를 알려 드릴게요.",
+        Text::BroadcastSent => "📢 공지를 보냈어요.",
+        Text::BanUsage => {
+            "🚫 차단할 사용자 ID를 입력해 주세요. 관리자는 차단할 수 없어요.\n\
+            예) /ban 123456789, /unban 123456789"
+        }
+        Text::UserBanned => "🚫 사용자를 차단했어요:",
+        Text::UserUnbanned => "✅ 사용자 차단을 해제했어요:",
+    }
+}
@@ -0,0 +1,114 @@
+use serde::{Deserialize, Serialize};
+
+mod en;
+mod ja;
+mod ko;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Locale {
+    Ko,
+    En,
+    Ja,
+}
+
+#[derive(Clone, Copy, Debug)]
+pub enum Text {
+    Help,
+    Cancelled,
+    EnterSearchTrackingNumber,
+    EnterTrackTrackingNumber,
+    NoSupportedCourier,
+    SelectSearchCourier,
+    SelectTrackCourier,
+    SelectTrackingNumber,
+    ServerError,
+    NoTrackingInfo,
+    RequestFailed,
+    WrongTrackingNumber,
+    ExpectedFormat,
+    NotExistsTrackingNumber,
+    RetryButton,
+    ChooseAnotherCourierButton,
+    TrackingStarted,
+    TrackingAlreadyExists,
+    TrackingListEmpty,
+    TrackingDetailUnavailable,
+    StopTrackingButton,
+    TrackingStopped,
+    NotTracking,
+    TrackUpdated,
+    TrackUpdateUsage,
+    TrackUpdateCourierRequired,
+    StopTrackingPermissionDenied,
+    NoInformation,
+    ShowAllButton,
+    TrackButton,
+    LiveTrackingButton,
+    CourierContact,
+    OpenHomepageButton,
+    OlderButton,
+    NewerButton,
+    LanguageUsage,
+    LanguageChanged,
+    TimezoneUsage,
+    TimezoneChanged,
+    InvalidTimezone,
+    OriginalTimeShown,
+    OriginalTimeHidden,
+    StatsUsers,
+    StatsTrackingChats,
+    StatsSubscriptions,
+    CourierHealthEmpty,
+    BroadcastUsage,
+    BroadcastStarted,
+    BroadcastSent,
+    BanUsage,
+    UserBanned,
+    UserUnbanned,
+}
+
+impl Default for Locale {
+    fn default() -> Self {
+        Locale::Ko
+    }
+}
+
+impl Locale {
+    pub fn from_code(code: &str) -> Option<Self> {
+        let language = code
+            .split(|c| c == '-' || c == '_')
+            .next()
+            .unwrap_or_default()
+            .to_ascii_lowercase();
+        match language.as_str() {
+            "ko" => Some(Locale::Ko),
+            "en" => Some(Locale::En),
+            "ja" => Some(Locale::Ja),
+            _ => None,
+        }
+    }
+
+    pub fn text(self, text: Text) -> &'static str {
+        match self {
+            Locale::Ko => ko::text(text),
+            Locale::En => en::text(text),
+            Locale::Ja => ja::text(text),
+        }
+    }
+
+    pub fn date_format(self) -> &'static str {
+        match self {
+            Locale::Ko => "%Y년 %m월 %d일",
+            Locale::En => "%B %-d, %Y",
+            Locale::Ja => "%Y年%m月%d日",
+        }
+    }
+
+    pub fn time_format(self) -> &'static str {
+        match self {
+            Locale::Ko => "%H시 %M분",
+            Locale::En => "%H:%M",
+            Locale::Ja => "%H時%M分",
+        }
+    }
+}
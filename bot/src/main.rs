@@ -1,60 +1,72 @@
 #![warn(clippy::all)]
 
-use std::env;
+use std::time::Duration;
 
-use actix_web::{get, post, web, App, HttpServer, Responder};
-use dialogue::{Dialogue, DialogueAnswerKind};
+use actix_web::{get, web, App, HttpRequest, HttpResponse, HttpServer, Responder};
+use config::{BotMode, Config, LogFormat};
 use dotenv::dotenv;
-use telbot_hyper::{
-    types::update::{Update, UpdateKind},
-    Api,
-};
+use state::AppState;
+use telbot_hyper::types::{update::Update, user::GetMe};
+use tokio::sync::watch;
+use tracing::{error, info, warn};
 
+mod admin_handler;
+mod barcode;
+mod callback_handler;
 mod command;
 mod command_handler;
+mod config;
+mod conversation;
 mod dialogue;
-mod dialogue_handler;
+mod discord;
+mod history;
+mod inline_handler;
+mod locale;
+mod metrics;
+mod polling;
+mod render;
+mod request_id;
+mod settings;
+mod slack;
+mod state;
 mod telegram;
+mod update_handler;
+mod webhook;
 
-#[post("/tg_webhook")]
-async fn tg_webhook(update: web::Json<Update>) -> impl Responder {
-    let api = Api::new(env::var("BOT_TOKEN").expect("env BOT_TOKEN is not set."));
-
-    if let UpdateKind::Message { message } = &update.kind {
-        if let Some(text) = message.kind.text() {
-            if text.starts_with("/") {
-                command_handler::handle_command(&api, &message, text).await;
-            } else if let Some(stage) = Dialogue::get(message.chat.id) {
-                dialogue_handler::handle_dialogue(
-                    &api,
-                    stage,
-                    DialogueAnswerKind::Message(text.to_string()),
-                )
-                .await;
-            } else {
-                let reply = &message.reply_text(text);
-                api.send_json(reply).await.expect("Failed to send message");
-            }
-        }
-    } else if let UpdateKind::CallbackQuery { callback_query } = &update.kind {
-        if let Some(message) = &callback_query.message {
-            if let Some(stage) = Dialogue::get(message.chat.id) {
-                let answer = callback_query
-                    .data
-                    .as_ref()
-                    .and_then(|str| Some(str.to_string()))
-                    .unwrap_or_default();
-                dialogue_handler::handle_dialogue(
-                    &api,
-                    stage,
-                    DialogueAnswerKind::CallbackQuery(answer),
-                )
-                .await;
-            }
-        }
+const SHUTDOWN_TIMEOUT: Duration = Duration::from_secs(25);
+
+async fn tg_webhook(
+    app: web::Data<AppState>,
+    request: HttpRequest,
+    update: web::Json<Update>,
+) -> HttpResponse {
+    let token = request
+        .headers()
+        .get(webhook::SECRET_TOKEN_HEADER)
+        .and_then(|value| value.to_str().ok());
+    if !webhook::verify_secret(app.config.webhook.secret.as_deref(), token) {
+        metrics::WEBHOOK_UPDATES
+            .with_label_values(&["unauthorized"])
+            .inc();
+        return HttpResponse::Unauthorized().finish();
     }
+    let claim = match webhook::claim_update(update.update_id) {
+        Some(claim) => claim,
+        None => {
+            metrics::WEBHOOK_UPDATES
+                .with_label_values(&["duplicate"])
+                .inc();
+            return HttpResponse::Ok().finish();
+        }
+    };
+    metrics::WEBHOOK_UPDATES
+        .with_label_values(&["accepted"])
+        .inc();
+
+    update_handler::handle_update(&app, &update).await;
+    claim.complete();
 
-    ""
+    HttpResponse::Ok().finish()
 }
 
 #[get("/health")]
@@ -62,9 +74,30 @@ async fn health() -> impl Responder {
     "OK"
 }
 
+#[get("/metrics")]
+async fn metrics_endpoint() -> impl Responder {
+    metrics::gather()
+}
+
+fn init_tracing(format: LogFormat) {
+    let filter = tracing_subscriber::EnvFilter::try_from_default_env()
+        .unwrap_or_else(|_| tracing_subscriber::EnvFilter::new("info"));
+    let subscriber = tracing_subscriber::fmt().with_env_filter(filter);
+    match format {
+        LogFormat::Json => subscriber.json().init(),
+        LogFormat::Text => subscriber.init(),
+    }
+}
+
+fn exit_with_error(context: &str, err: impl std::fmt::Display) -> ! {
+    eprintln!("{}: {}", context, err);
+    std::process::exit(1)
+}
+
 fn main() {
     dotenv().ok();
-    env_logger::init_from_env(env_logger::Env::new().default_filter_or("info"));
+    let config = Config::load().unwrap_or_else(|err| exit_with_error("Invalid configuration", err));
+    init_tracing(config.log_format);
     actix_web::rt::System::with_tokio_rt(|| {
         tokio::runtime::Builder::new_multi_thread()
             .enable_all()
@@ -73,21 +106,108 @@ fn main() {
             .build()
             .unwrap()
     })
-    .block_on(run())
+    .block_on(run(config))
 }
 
-async fn run() {
-    let port = env::var("PORT").expect("env PORT is not set.");
-    HttpServer::new(|| {
-        App::new()
+async fn run(config: Config) {
+    let app =
+        AppState::new(config).unwrap_or_else(|err| exit_with_error("Invalid configuration", err));
+    let app = web::Data::new(app);
+    let me = app
+        .api
+        .send_json(&GetMe)
+        .await
+        .expect("Failed to get bot info");
+    if let Some(username) = me.username {
+        telegram::BOT_USERNAME.set(username).ok();
+    }
+
+    let (shutdown_sender, shutdown_receiver) = watch::channel(false);
+    let bot_mode = app.config.mode;
+    let polling = match bot_mode {
+        BotMode::Webhook => {
+            if let Some(url) = &app.config.webhook.url {
+                webhook::set_webhook(&app.api, url, &app.config.webhook).await;
+            }
+            None
+        }
+        BotMode::Polling => Some(actix_web::rt::spawn(polling::run(
+            app.clone(),
+            shutdown_receiver,
+        ))),
+    };
+
+    if app.config.discord.is_enabled() {
+        discord::register_commands(&app.config.discord).await;
+    }
+
+    if let Some(metrics_addr) = app.config.metrics_addr {
+        info!("Serving metrics on {}", metrics_addr);
+        let metrics_server = HttpServer::new(|| App::new().service(metrics_endpoint))
+            .workers(1)
+            .disable_signals()
+            .bind(metrics_addr)
+            .unwrap_or_else(|err| exit_with_error("Couldn't bind metrics address", err));
+        actix_web::rt::spawn(async move {
+            if let Err(err) = metrics_server.run().await {
+                error!("Metrics server stopped: {}", err);
+            }
+        });
+    }
+
+    let webhook_path = app.config.webhook.path.clone();
+    let port = app.config.port;
+    let server_app = app.clone();
+    HttpServer::new(move || {
+        let config = &server_app.config;
+        let mut service = App::new()
+            .app_data(server_app.clone())
             .wrap(actix_web::middleware::Logger::default())
-            .service(tg_webhook)
-            .service(health)
+            .service(health);
+        if config.discord.is_enabled() {
+            service = service.service(
+                web::resource(&config.discord.path).route(web::post().to(discord::interactions)),
+            );
+        }
+        if config.slack.is_enabled() {
+            service = service
+                .service(
+                    web::resource(&config.slack.commands_path)
+                        .route(web::post().to(slack::commands)),
+                )
+                .service(
+                    web::resource(&config.slack.interactions_path)
+                        .route(web::post().to(slack::interactions)),
+                );
+        }
+        match bot_mode {
+            BotMode::Webhook => {
+                service.service(web::resource(&webhook_path).route(web::post().to(tg_webhook)))
+            }
+            BotMode::Polling => service,
+        }
     })
     .workers(2)
+    .shutdown_timeout(SHUTDOWN_TIMEOUT.as_secs())
     .bind(format!("0.0.0.0:{}", port))
     .expect(&format!("Couldn't bind to port {}", port))
     .run()
     .await
-    .unwrap()
+    .unwrap();
+
+    info!("HTTP server stopped, draining...");
+    shutdown_sender.send(true).ok();
+    if let Some(polling) = polling {
+        if tokio::time::timeout(SHUTDOWN_TIMEOUT, polling)
+            .await
+            .is_err()
+        {
+            warn!("Polling did not finish within {:?}", SHUTDOWN_TIMEOUT);
+        }
+    }
+
+    if let Err(err) = app.settings.flush() {
+        error!("Failed to flush settings store: {}", err);
+    }
+    info!("Bot stopped");
 }
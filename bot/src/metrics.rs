@@ -0,0 +1,80 @@
+use once_cell::sync::Lazy;
+use prometheus::{
+    register_int_counter_vec, register_int_gauge, Encoder, IntCounterVec, IntGauge, TextEncoder,
+};
+use telbot_hyper::{types::JsonMethod, Api};
+
+use crate::dialogue::Dialogue;
+
+pub static WEBHOOK_UPDATES: Lazy<IntCounterVec> = Lazy::new(|| {
+    register_int_counter_vec!(
+        "bot_webhook_updates_total",
+        "Number of webhook requests by result",
+        &["result"]
+    )
+    .unwrap()
+});
+
+pub static UPDATES: Lazy<IntCounterVec> = Lazy::new(|| {
+    register_int_counter_vec!(
+        "bot_updates_total",
+        "Number of handled updates by kind",
+        &["kind"]
+    )
+    .unwrap()
+});
+
+pub static COMMANDS: Lazy<IntCounterVec> = Lazy::new(|| {
+    register_int_counter_vec!(
+        "bot_commands_total",
+        "Number of handled commands",
+        &["command"]
+    )
+    .unwrap()
+});
+
+pub static TELEGRAM_API_ERRORS: Lazy<IntCounterVec> = Lazy::new(|| {
+    register_int_counter_vec!(
+        "bot_telegram_api_errors_total",
+        "Number of failed Telegram Bot API calls by method",
+        &["method"]
+    )
+    .unwrap()
+});
+
+pub static ACTIVE_DIALOGUES: Lazy<IntGauge> = Lazy::new(|| {
+    register_int_gauge!("bot_active_dialogues", "Number of ongoing dialogues").unwrap()
+});
+
+pub struct MeteredApi {
+    inner: Api,
+}
+
+impl MeteredApi {
+    pub fn new(inner: Api) -> Self {
+        Self { inner }
+    }
+
+    pub async fn send_json<Method: JsonMethod>(
+        &self,
+        method: &Method,
+    ) -> telbot_hyper::Result<Method::Response> {
+        let result = self.inner.send_json(method).await;
+        if result.is_err() {
+            TELEGRAM_API_ERRORS
+                .with_label_values(&[Method::name()])
+                .inc();
+        }
+        result
+    }
+}
+
+pub fn gather() -> String {
+    ACTIVE_DIALOGUES.set(Dialogue::count() as i64);
+
+    let mut buffer = Vec::new();
+    TextEncoder::new()
+        .encode(&prometheus::gather(), &mut buffer)
+        .expect("Failed to encode metrics");
+    String::from_utf8(buffer).expect("Metrics are not valid UTF-8")
+}
@@ -0,0 +1,96 @@
+use std::time::Duration;
+
+use actix_web::web;
+use futures::{stream::FuturesUnordered, FutureExt, StreamExt};
+use telbot_hyper::types::update::{DeleteWebhook, GetUpdates};
+use tokio::{sync::watch, task::JoinError};
+use tracing::{error, info};
+
+use crate::{state::AppState, update_handler};
+
+const MAX_RETRY_DELAY: Duration = Duration::from_secs(60);
+
+pub async fn run(app: web::Data<AppState>, mut shutdown: watch::Receiver<bool>) {
+    if !delete_webhook(&app, &mut shutdown).await {
+        info!("Polling stopped");
+        return;
+    }
+
+    let mut handling = FuturesUnordered::new();
+    let mut offset = None;
+    loop {
+        while let Some(Some(result)) = handling.next().now_or_never() {
+            log_failure(result);
+        }
+
+        let mut get_updates = GetUpdates::new().with_timeout(app.config.polling.timeout_secs);
+        if let Some(offset) = offset {
+            get_updates = get_updates.with_offset(offset);
+        }
+
+        let result = tokio::select! {
+            _ = shutdown.changed() => break,
+            result = app.api.send_json(&get_updates) => result,
+        };
+        let updates = match result {
+            Ok(updates) => updates,
+            Err(err) => {
+                error!("Failed to get updates: {:?}", err);
+                tokio::select! {
+                    _ = shutdown.changed() => break,
+                    _ = tokio::time::sleep(app.config.polling.retry_delay()) => continue,
+                }
+            }
+        };
+
+        for update in updates {
+            offset = Some(update.update_id + 1);
+            // A slow or panicking update must not hold up the ones behind it.
+            let app = app.clone();
+            handling.push(actix_web::rt::spawn(async move {
+                update_handler::handle_update(&app, &update).await
+            }));
+        }
+
+        if *shutdown.borrow() {
+            break;
+        }
+    }
+
+    while let Some(result) = handling.next().await {
+        log_failure(result);
+    }
+    if let Some(offset) = offset {
+        let confirm = GetUpdates::new().with_offset(offset).with_timeout(0);
+        if let Err(err) = app.api.send_json(&confirm).await {
+            error!("Failed to confirm handled updates: {:?}", err);
+        }
+    }
+    info!("Polling stopped");
+}
+
+/// Polling fails while a webhook is set, so keep trying with backoff
+/// instead of giving up on a transient API error.
+async fn delete_webhook(app: &AppState, shutdown: &mut watch::Receiver<bool>) -> bool {
+    let mut delay = app.config.polling.retry_delay().max(Duration::from_secs(1));
+    loop {
+        match app.api.send_json(&DeleteWebhook::new()).await {
+            Ok(_) => return true,
+            Err(err) => error!(
+                "Failed to delete webhook, retrying in {:?}: {:?}",
+                delay, err
+            ),
+        }
+        tokio::select! {
+            _ = shutdown.changed() => return false,
+            _ = tokio::time::sleep(delay) => {}
+        }
+        delay = (delay * 2).min(MAX_RETRY_DELAY);
+    }
+}
+
+fn log_failure(result: Result<(), JoinError>) {
+    if let Err(err) = result {
+        error!("Update handler failed: {}", err);
+    }
+}
@@ -0,0 +1,623 @@
+use bot::tracker::{
+    CourierContact, ExtractedTrackingNumber, SearchResponse, StatusKind, SupportCouriersResponse,
+    TrackingDetail, TrackingInfo, TrackingListEntry,
+};
+use chrono::{DateTime, FixedOffset, Offset, TimeZone};
+use chrono_tz::{Asia::Seoul, Tz};
+
+use crate::{
+    dialogue::TypeKind,
+    locale::{Locale, Text},
+    settings::UserSettings,
+};
+
+/// Text formatting of a chat platform.
+pub trait Markup {
+    const MESSAGE_LIMIT: usize;
+
+    fn escape(text: &str) -> String;
+
+    fn bold(text: &str) -> String;
+}
+
+pub struct Reply {
+    pub text: String,
+    pub buttons: Vec<Vec<Button>>,
+}
+
+impl Reply {
+    pub fn text(text: String) -> Self {
+        Self {
+            text,
+            buttons: Vec::new(),
+        }
+    }
+
+    pub fn with_buttons(text: String, buttons: Vec<Vec<Button>>) -> Self {
+        Self { text, buttons }
+    }
+}
+
+#[derive(Clone)]
+pub struct Button {
+    pub label: String,
+    pub action: ButtonAction,
+}
+
+#[derive(Clone)]
+pub enum ButtonAction {
+    Callback(String),
+    Url(String),
+}
+
+impl Button {
+    pub fn callback(label: impl Into<String>, data: String) -> Self {
+        Self {
+            label: label.into(),
+            action: ButtonAction::Callback(data),
+        }
+    }
+
+    pub fn url(label: impl Into<String>, url: String) -> Self {
+        Self {
+            label: label.into(),
+            action: ButtonAction::Url(url),
+        }
+    }
+}
+
+fn create_info_header_message<M: Markup>(info: &TrackingInfo, locale: Locale) -> String {
+    let no_information = locale.text(Text::NoInformation);
+    format!(
+        "📦 {name} {tracking_number}\n\
+        {sender} ▶️ {receiver}{product}",
+        name = M::bold(&info.name),
+        tracking_number = M::escape(&info.tracking_number),
+        sender = M::escape(info.sender.as_deref().unwrap_or(no_information)),
+        receiver = M::escape(info.receiver.as_deref().unwrap_or(no_information)),
+        product = if let Some(product) = &info.product {
+            M::escape(&format!(" ({})", product))
+        } else {
+            "".to_string()
+        }
+    )
+}
+
+/// The event time in the user's time zone, and as the courier reported it.
+fn parse_time(
+    time: &str,
+    settings: &UserSettings,
+) -> Option<(DateTime<Tz>, DateTime<FixedOffset>)> {
+    let original = DateTime::parse_from_rfc3339(time).ok().or_else(|| {
+        // Servers before offsets were kept sent Korean time without one.
+        Seoul
+            .datetime_from_str(time, "%Y-%m-%d %H:%M:%S")
+            .ok()
+            .map(|datetime| datetime.with_timezone(&datetime.offset().fix()))
+    })?;
+    Some((original.with_timezone(&settings.timezone()), original))
+}
+
+fn format_date(time: &str, settings: &UserSettings) -> String {
+    match parse_time(time, settings) {
+        Some((datetime, _)) => datetime.format(settings.locale().date_format()).to_string(),
+        None => time.to_string(),
+    }
+}
+
+fn create_detail_message<M: Markup>(detail: &TrackingDetail, settings: &UserSettings) -> String {
+    let time = match parse_time(&detail.time, settings) {
+        Some((datetime, original)) => {
+            let time = datetime.format(settings.locale().time_format()).to_string();
+            if settings.show_original_time && datetime.offset().fix() != *original.offset() {
+                format!("{} / {}", time, original.format("%H:%M UTC%:z"))
+            } else {
+                time
+            }
+        }
+        None => detail.time.clone(),
+    };
+
+    if detail.message.is_some() && detail.status.is_some() && detail.location.is_some() {
+        M::escape(&format!(
+            "{} ({}, {}): {}",
+            detail.status(),
+            time,
+            detail.location(),
+            detail.message(),
+        ))
+    } else if detail.message.is_none() && detail.status.is_some() && detail.location.is_some() {
+        M::escape(&format!(
+            "{}: [{}] {}",
+            time,
+            detail.location(),
+            detail.status(),
+        ))
+    } else if detail.message.is_some() && detail.status.is_none() && detail.location.is_some() {
+        M::escape(&format!(
+            "{}: [{}] {}",
+            time,
+            detail.location(),
+            detail.message(),
+        ))
+    } else if detail.message.is_some() && detail.status.is_none() && detail.location.is_none() {
+        M::escape(&format!("{}: {}", time, detail.message()))
+    } else {
+        String::new()
+    }
+}
+
+fn create_courier_contact_message<M: Markup>(contact: &CourierContact, locale: Locale) -> String {
+    let contact = match (&contact.name, &contact.phone) {
+        (Some(name), Some(phone)) => format!("{} ({})", name, phone),
+        (Some(name), None) => name.clone(),
+        (None, Some(phone)) => phone.clone(),
+        (None, None) => return String::new(),
+    };
+    M::escape(&format!(
+        "\n{} {}",
+        locale.text(Text::CourierContact),
+        contact
+    ))
+}
+
+pub fn create_simple_tracking_message<M: Markup>(
+    response: &SearchResponse,
+    settings: &UserSettings,
+) -> String {
+    let locale = settings.locale();
+    match response.status() {
+        StatusKind::Ok => {
+            let info = response.tracking_info.as_ref().unwrap();
+            let header = create_info_header_message::<M>(info, locale);
+            if info.tracks.len() == 0 {
+                return header;
+            }
+            let last_detail = info.tracks.last().unwrap();
+
+            let mut detail_message = create_detail_message::<M>(last_detail, settings);
+            if let Some(contact) = &last_detail.courier_contact {
+                detail_message.push_str(&create_courier_contact_message::<M>(contact, locale));
+            }
+
+            format!(
+                "{}\n\
+                \n\
+                {}\n\
+                {}",
+                header,
+                M::bold(&format_date(&last_detail.time, settings)),
+                detail_message
+            )
+        }
+        StatusKind::RequestFailed => M::escape(locale.text(Text::RequestFailed)),
+        StatusKind::WrongTrackingNumber => match response.message.as_deref() {
+            Some(format) if !format.is_empty() => M::escape(&format!(
+                "{}\n{} {}",
+                locale.text(Text::WrongTrackingNumber),
+                locale.text(Text::ExpectedFormat),
+                format
+            )),
+            _ => M::escape(locale.text(Text::WrongTrackingNumber)),
+        },
+        StatusKind::NotExistsTrackingNumber => {
+            M::escape(locale.text(Text::NotExistsTrackingNumber))
+        }
+        StatusKind::TrackingAlreadyExists => M::escape(locale.text(Text::TrackingAlreadyExists)),
+        StatusKind::TrackingNotExists => M::escape(locale.text(Text::NotTracking)),
+        StatusKind::PermissionDenied => M::escape(locale.text(Text::StopTrackingPermissionDenied)),
+    }
+}
+
+pub fn create_search_result<M: Markup>(
+    response: &SearchResponse,
+    courier_id: &str,
+    tracking_number: &str,
+    settings: &UserSettings,
+) -> Reply {
+    let locale = settings.locale();
+    let buttons = match (response.status(), &response.tracking_info) {
+        (StatusKind::Ok, Some(info)) => {
+            create_search_result_buttons(info, !info.is_delivered, locale)
+        }
+        _ => create_search_error_buttons(TypeKind::Search, courier_id, tracking_number, locale),
+    };
+    Reply::with_buttons(
+        create_simple_tracking_message::<M>(response, settings),
+        buttons,
+    )
+}
+
+fn create_history_page<M: Markup>(
+    header: &str,
+    entries: &[(String, String)],
+    footer: &str,
+) -> String {
+    let mut message = header.to_string();
+    let mut current_date = None;
+    for (date, line) in entries {
+        if current_date != Some(date) {
+            message.push_str(&format!("\n\n{}", M::bold(date)));
+            current_date = Some(date);
+        }
+        message.push('\n');
+        message.push_str(line);
+    }
+    message.push_str(footer);
+    message
+}
+
+pub fn create_history_pages<M: Markup>(
+    info: &TrackingInfo,
+    settings: &UserSettings,
+) -> Vec<String> {
+    let locale = settings.locale();
+    let header = create_info_header_message::<M>(info, locale);
+    let entries = info
+        .tracks
+        .iter()
+        .map(|detail| {
+            (
+                format_date(&detail.time, settings),
+                create_detail_message::<M>(detail, settings),
+            )
+        })
+        .collect::<Vec<(String, String)>>();
+
+    let reserved_footer = M::escape(&format!("\n\n({}/{})", entries.len(), entries.len()));
+    let mut pages: Vec<&[(String, String)]> = Vec::new();
+    let mut end = entries.len();
+    while end > 0 {
+        let mut start = end - 1;
+        while start > 0
+            && create_history_page::<M>(&header, &entries[start - 1..end], &reserved_footer).len()
+                <= M::MESSAGE_LIMIT
+        {
+            start -= 1;
+        }
+        pages.push(&entries[start..end]);
+        end = start;
+    }
+
+    if pages.is_empty() {
+        return vec![header];
+    }
+
+    let total = pages.len();
+    pages
+        .iter()
+        .enumerate()
+        .map(|(page, entries)| {
+            let footer = if total > 1 {
+                M::escape(&format!("\n\n({}/{})", total - page, total))
+            } else {
+                String::new()
+            };
+            create_history_page::<M>(&header, entries, &footer)
+        })
+        .collect()
+}
+
+pub fn create_history_buttons(
+    info: &TrackingInfo,
+    page: usize,
+    total: usize,
+    locale: Locale,
+) -> Vec<Vec<Button>> {
+    let mut rows = Vec::new();
+
+    let mut row = Vec::new();
+    if page + 1 < total {
+        row.push(Button::callback(
+            locale.text(Text::OlderButton),
+            format!("history:{}:{}:{}", info.id, info.tracking_number, page + 1),
+        ));
+    }
+    if page > 0 {
+        row.push(Button::callback(
+            locale.text(Text::NewerButton),
+            format!("history:{}:{}:{}", info.id, info.tracking_number, page - 1),
+        ));
+    }
+    if !row.is_empty() {
+        rows.push(row);
+    }
+
+    rows.push(vec![Button::url(
+        locale.text(Text::OpenHomepageButton),
+        info.url.clone(),
+    )]);
+
+    rows
+}
+
+pub fn create_tracking_list_message<M: Markup>(
+    entries: &[TrackingListEntry],
+    settings: &UserSettings,
+) -> String {
+    let locale = settings.locale();
+    if entries.is_empty() {
+        return M::escape(locale.text(Text::TrackingListEmpty));
+    }
+
+    entries
+        .iter()
+        .filter_map(|entry| entry.tracking_info.as_ref().map(|info| (entry, info)))
+        .map(|(entry, info)| {
+            let name = if info.name.is_empty() {
+                &info.id
+            } else {
+                &info.name
+            };
+            let mut header = match &entry.label {
+                Some(label) => format!(
+                    "📦 {}\n{} {}",
+                    M::bold(label),
+                    M::escape(name),
+                    M::escape(&info.tracking_number)
+                ),
+                None => format!("📦 {} {}", M::bold(name), M::escape(&info.tracking_number)),
+            };
+            if !entry.tags.is_empty() {
+                let tags = entry
+                    .tags
+                    .iter()
+                    .map(|tag| format!("#{}", tag))
+                    .collect::<Vec<String>>()
+                    .join(" ");
+                header = format!("{}\n{}", header, M::escape(&tags));
+            }
+            if let Some(note) = &entry.note {
+                header = format!("{}\n📝 {}", header, M::escape(note));
+            }
+
+            match info.tracks.last() {
+                Some(detail) => format!(
+                    "{}\n{}",
+                    header,
+                    create_detail_message::<M>(detail, settings)
+                ),
+                None => format!(
+                    "{}\n{}",
+                    header,
+                    M::escape(locale.text(Text::TrackingDetailUnavailable))
+                ),
+            }
+        })
+        .collect::<Vec<String>>()
+        .join("\n\n")
+}
+
+pub fn create_tracking_list_buttons(
+    entries: &[TrackingListEntry],
+    locale: Locale,
+) -> Vec<Vec<Button>> {
+    entries
+        .iter()
+        .filter_map(|entry| entry.tracking_info.as_ref().map(|info| (entry, info)))
+        .map(|(entry, info)| {
+            vec![Button::callback(
+                format!(
+                    "🗑 {} {}",
+                    entry.label.as_ref().unwrap_or(&info.tracking_number),
+                    locale.text(Text::StopTrackingButton)
+                ),
+                format!("untrack:{}:{}", info.id, info.tracking_number),
+            )]
+        })
+        .collect()
+}
+
+pub fn create_search_result_buttons(
+    info: &TrackingInfo,
+    can_track: bool,
+    locale: Locale,
+) -> Vec<Vec<Button>> {
+    let mut rows = Vec::new();
+
+    let mut row = vec![Button::callback(
+        locale.text(Text::ShowAllButton),
+        format!("show_all:{}:{}", info.id, info.tracking_number),
+    )];
+    if can_track {
+        row.push(Button::callback(
+            locale.text(Text::TrackButton),
+            format!("track:{}", info.tracking_number),
+        ));
+    }
+    rows.push(row);
+
+    if let Some(url) = info
+        .tracks
+        .last()
+        .and_then(|detail| detail.live_tracking_url.clone())
+    {
+        rows.push(vec![Button::url(
+            locale.text(Text::LiveTrackingButton),
+            url,
+        )]);
+    }
+
+    rows.push(vec![Button::url(
+        locale.text(Text::OpenHomepageButton),
+        info.url.clone(),
+    )]);
+
+    rows
+}
+
+/// Retries with the same courier or asks for another one, for a search or a track alike.
+pub fn create_search_error_buttons(
+    kind: TypeKind,
+    courier_id: &str,
+    tracking_number: &str,
+    locale: Locale,
+) -> Vec<Vec<Button>> {
+    vec![vec![
+        Button::callback(
+            locale.text(Text::RetryButton),
+            format!("{}:{}:{}", kind.run_action(), courier_id, tracking_number),
+        ),
+        Button::callback(
+            locale.text(Text::ChooseAnotherCourierButton),
+            format!("{}:{}", kind.as_str(), tracking_number),
+        ),
+    ]]
+}
+
+/// Two couriers per row; `data` builds the callback data for each courier id.
+pub fn create_courier_buttons(
+    support_couriers: &SupportCouriersResponse,
+    data: impl Fn(&str) -> String,
+) -> Vec<Vec<Button>> {
+    support_couriers
+        .couriers
+        .chunks(2)
+        .map(|couriers| {
+            couriers
+                .iter()
+                .map(|courier| Button::callback(courier.name.clone(), data(&courier.id)))
+                .collect()
+        })
+        .collect()
+}
+
+/// One candidate per row; `data` builds the callback data for each tracking number.
+pub fn create_tracking_number_buttons(
+    candidates: &[ExtractedTrackingNumber],
+    data: impl Fn(&str) -> String,
+) -> Vec<Vec<Button>> {
+    candidates
+        .iter()
+        .map(|candidate| {
+            let couriers = candidate
+                .couriers
+                .iter()
+                .map(|courier| courier.name.as_str())
+                .collect::<Vec<&str>>()
+                .join(", ");
+            vec![Button::callback(
+                format!("{} ({})", candidate.tracking_number, couriers),
+                data(&candidate.tracking_number),
+            )]
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct Plain;
+
+    impl Markup for Plain {
+        const MESSAGE_LIMIT: usize = 300;
+
+        fn escape(text: &str) -> String {
+            text.to_string()
+        }
+
+        fn bold(text: &str) -> String {
+            format!("*{}*", text)
+        }
+    }
+
+    fn info(tracks: usize) -> TrackingInfo {
+        TrackingInfo {
+            id: "cj".to_string(),
+            name: "CJ".to_string(),
+            url: "https://example.com".to_string(),
+            tracking_number: "1234".to_string(),
+            tracks: (0..tracks)
+                .map(|day| TrackingDetail {
+                    time: format!("2021-01-{:02}T10:00:00+09:00", day + 1),
+                    status: Some(format!("status {:02}", day)),
+                    location: Some("Seoul".to_string()),
+                    message: Some("Moving".to_string()),
+                    ..Default::default()
+                })
+                .collect(),
+            ..Default::default()
+        }
+    }
+
+    fn callbacks(buttons: &[Vec<Button>]) -> Vec<&str> {
+        buttons
+            .iter()
+            .flatten()
+            .filter_map(|button| match &button.action {
+                ButtonAction::Callback(data) => Some(data.as_str()),
+                ButtonAction::Url(_) => None,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn splits_history_into_pages_within_the_limit() {
+        let pages = create_history_pages::<Plain>(&info(20), &UserSettings::default());
+        let total = pages.len();
+        assert!(total > 1);
+        assert!(pages.iter().all(|page| page.len() <= Plain::MESSAGE_LIMIT));
+        assert!(pages[0].contains("status 19"));
+        assert!(pages[0].ends_with(&format!("({}/{})", total, total)));
+        assert!(pages[total - 1].contains("status 00"));
+        assert!(pages[total - 1].ends_with(&format!("(1/{})", total)));
+        for day in 0..20 {
+            let status = format!("status {:02}", day);
+            assert_eq!(
+                pages.iter().filter(|page| page.contains(&status)).count(),
+                1
+            );
+        }
+    }
+
+    #[test]
+    fn short_history_has_no_page_footer() {
+        let pages = create_history_pages::<Plain>(&info(2), &UserSettings::default());
+        assert_eq!(pages.len(), 1);
+        assert!(pages[0].contains("status 00") && pages[0].contains("status 01"));
+        assert!(!pages[0].contains("(1/1)"));
+    }
+
+    #[test]
+    fn history_buttons_turn_pages() {
+        let info = info(20);
+        let locale = Locale::default();
+        assert_eq!(
+            callbacks(&create_history_buttons(&info, 0, 3, locale)),
+            ["history:cj:1234:1"]
+        );
+        assert_eq!(
+            callbacks(&create_history_buttons(&info, 1, 3, locale)),
+            ["history:cj:1234:2", "history:cj:1234:0"]
+        );
+        assert_eq!(
+            callbacks(&create_history_buttons(&info, 2, 3, locale)),
+            ["history:cj:1234:1"]
+        );
+        assert!(callbacks(&create_history_buttons(&info, 0, 1, locale)).is_empty());
+    }
+
+    #[test]
+    fn search_error_buttons_keep_the_kind() {
+        let locale = Locale::default();
+        assert_eq!(
+            callbacks(&create_search_error_buttons(
+                TypeKind::Search,
+                "cj",
+                "1234",
+                locale
+            )),
+            ["retry:cj:1234", "search:1234"]
+        );
+        assert_eq!(
+            callbacks(&create_search_error_buttons(
+                TypeKind::Track,
+                "cj",
+                "1234",
+                locale
+            )),
+            ["add:cj:1234", "track:1234"]
+        );
+    }
+}
@@ -0,0 +1,9 @@
+pub const REQUEST_ID_HEADER: &str = "x-request-id";
+
+tokio::task_local! {
+    pub static REQUEST_ID: String;
+}
+
+pub fn for_update(update_id: u32) -> String {
+    format!("tg-{}", update_id)
+}
@@ -0,0 +1,125 @@
+use std::{
+    collections::HashMap,
+    fs,
+    io::{self, Write},
+    path::{Path, PathBuf},
+    sync::RwLock,
+};
+
+use chrono_tz::{Asia::Seoul, Tz};
+use serde::{Deserialize, Serialize};
+
+use crate::locale::Locale;
+
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct UserSettings {
+    pub language_code: Option<String>,
+    pub locale: Option<Locale>,
+    pub timezone: Option<String>,
+    #[serde(default)]
+    pub show_original_time: bool,
+    #[serde(default)]
+    pub banned: bool,
+}
+
+impl UserSettings {
+    pub fn locale(&self) -> Locale {
+        self.locale
+            .or_else(|| self.language_code.as_deref().and_then(Locale::from_code))
+            .unwrap_or_default()
+    }
+
+    pub fn timezone(&self) -> Tz {
+        self.timezone
+            .as_deref()
+            .and_then(|timezone| timezone.parse().ok())
+            .unwrap_or(Seoul)
+    }
+}
+
+#[derive(Default)]
+pub struct SettingsStore {
+    path: Option<PathBuf>,
+    settings: RwLock<HashMap<i64, UserSettings>>,
+}
+
+impl SettingsStore {
+    pub fn open<P: AsRef<Path>>(path: P) -> io::Result<Self> {
+        let path = path.as_ref().to_path_buf();
+        let settings = if path.exists() {
+            serde_json::from_str(&fs::read_to_string(&path)?)
+                .map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))?
+        } else {
+            HashMap::new()
+        };
+
+        Ok(Self {
+            path: Some(path),
+            settings: RwLock::new(settings),
+        })
+    }
+
+    pub fn get(&self, user_id: i64) -> UserSettings {
+        self.settings
+            .read()
+            .ok()
+            .and_then(|map| map.get(&user_id).cloned())
+            .unwrap_or_default()
+    }
+
+    pub fn user_ids(&self) -> Vec<i64> {
+        self.settings
+            .read()
+            .map(|map| map.keys().copied().collect())
+            .unwrap_or_default()
+    }
+
+    pub fn locale(&self, user_id: i64) -> Locale {
+        self.get(user_id).locale()
+    }
+
+    pub fn update<F>(&self, user_id: i64, f: F) -> io::Result<()>
+    where
+        F: FnOnce(&mut UserSettings),
+    {
+        let changed = match self.settings.write() {
+            Ok(mut map) => {
+                let settings = map.entry(user_id).or_default();
+                let old = settings.clone();
+                f(settings);
+                *settings != old
+            }
+            Err(_) => return Err(io::Error::new(io::ErrorKind::Other, "store is poisoned")),
+        };
+
+        if changed {
+            self.flush()
+        } else {
+            Ok(())
+        }
+    }
+
+    pub fn flush(&self) -> io::Result<()> {
+        let path = match &self.path {
+            Some(path) => path,
+            None => return Ok(()),
+        };
+        let json = match self.settings.read() {
+            Ok(map) => serde_json::to_string(&*map)?,
+            Err(_) => return Err(io::Error::new(io::ErrorKind::Other, "store is poisoned")),
+        };
+        write_atomically(path, &json)
+    }
+}
+
+/// Writes `<path>.tmp`, syncs it and renames it over `path`.
+fn write_atomically(path: &Path, contents: &str) -> io::Result<()> {
+    let mut tmp_path = path.as_os_str().to_owned();
+    tmp_path.push(".tmp");
+    let tmp_path = PathBuf::from(tmp_path);
+
+    let mut file = fs::File::create(&tmp_path)?;
+    file.write_all(contents.as_bytes())?;
+    file.sync_all()?;
+    fs::rename(&tmp_path, path)
+}
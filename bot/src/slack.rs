@@ -0,0 +1,393 @@
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use actix_web::{web, HttpRequest, HttpResponse};
+use hmac::{Hmac, Mac};
+use once_cell::sync::Lazy;
+use serde::Deserialize;
+use serde_json::{json, Value};
+use sha2::Sha256;
+use tracing::warn;
+
+use crate::{
+    command::{Args, Command},
+    conversation::{self, Session, COMMANDS},
+    metrics,
+    render::{Button, ButtonAction, Markup, Reply},
+    settings::UserSettings,
+    state::AppState,
+};
+
+const SIGNATURE_HEADER: &str = "X-Slack-Signature";
+const TIMESTAMP_HEADER: &str = "X-Slack-Request-Timestamp";
+const MAX_REQUEST_AGE_SECS: i64 = 60 * 5;
+const MAX_BLOCKS: usize = 50;
+const MAX_ELEMENTS_PER_ACTIONS: usize = 25;
+const MAX_BUTTON_LABEL_LENGTH: usize = 75;
+const MAX_SECTION_TEXT_LENGTH: usize = 3000;
+
+static CLIENT: Lazy<reqwest::Client> = Lazy::new(reqwest::Client::new);
+
+pub struct SlackMrkdwn;
+
+impl Markup for SlackMrkdwn {
+    const MESSAGE_LIMIT: usize = 2900;
+
+    fn escape(text: &str) -> String {
+        text.replace('&', "&amp;")
+            .replace('<', "&lt;")
+            .replace('>', "&gt;")
+    }
+
+    fn bold(text: &str) -> String {
+        format!("*{}*", Self::escape(text))
+    }
+}
+
+#[derive(Deserialize)]
+struct SlashCommand {
+    team_id: String,
+    channel_id: String,
+    user_id: String,
+    command: String,
+    #[serde(default)]
+    text: String,
+    response_url: String,
+}
+
+#[derive(Deserialize)]
+struct InteractionForm {
+    payload: String,
+}
+
+#[derive(Deserialize)]
+struct BlockActions {
+    team: Id,
+    channel: Option<Id>,
+    user: Id,
+    response_url: Option<String>,
+    #[serde(default)]
+    actions: Vec<Action>,
+}
+
+#[derive(Deserialize)]
+struct Id {
+    id: String,
+}
+
+#[derive(Deserialize)]
+struct Action {
+    value: Option<String>,
+}
+
+pub fn tracker_user_id(team_id: &str, channel_id: &str) -> String {
+    format!("slack:{}:{}", team_id, channel_id)
+}
+
+fn session(team_id: &str, channel_id: &str, user_id: &str) -> Session {
+    Session {
+        user_id: tracker_user_id(team_id, channel_id),
+        requested_by: user_id.to_string(),
+        // Direct message channel ids start with D.
+        is_chat_admin: channel_id.starts_with('D'),
+        settings: UserSettings::default(),
+        dialogue: false,
+    }
+}
+
+fn verify_signature(signing_secret: &str, request: &HttpRequest, body: &[u8]) -> bool {
+    let header = |name: &str| {
+        request
+            .headers()
+            .get(name)
+            .and_then(|value| value.to_str().ok())
+    };
+    let (signature, timestamp) = match (header(SIGNATURE_HEADER), header(TIMESTAMP_HEADER)) {
+        (Some(signature), Some(timestamp)) => (signature, timestamp),
+        _ => return false,
+    };
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|duration| duration.as_secs() as i64)
+        .unwrap_or_default();
+    match timestamp.parse::<i64>() {
+        Ok(timestamp) if (now - timestamp).abs() <= MAX_REQUEST_AGE_SECS => {}
+        _ => return false,
+    }
+    let signature = match signature
+        .strip_prefix("v0=")
+        .and_then(|signature| hex::decode(signature).ok())
+    {
+        Some(signature) => signature,
+        None => return false,
+    };
+
+    let mut mac = Hmac::<Sha256>::new_from_slice(signing_secret.as_bytes())
+        .expect("HMAC accepts keys of any size");
+    mac.update(format!("v0:{}:", timestamp).as_bytes());
+    mac.update(body);
+    mac.verify_slice(&signature).is_ok()
+}
+
+/// Splits `text` into chunks that fit a section, between lines unless a line alone is too long.
+fn sections(text: &str) -> Vec<String> {
+    let mut sections = Vec::new();
+    let mut section = String::new();
+    let mut length = 0;
+    for line in text.split_inclusive('\n') {
+        let line_length = line.chars().count();
+        if length > 0 && length + line_length > MAX_SECTION_TEXT_LENGTH {
+            sections.push(std::mem::take(&mut section));
+            length = 0;
+        }
+        for c in line.chars() {
+            if length == MAX_SECTION_TEXT_LENGTH {
+                sections.push(std::mem::take(&mut section));
+                length = 0;
+            }
+            section.push(c);
+            length += 1;
+        }
+    }
+    if !section.is_empty() || sections.is_empty() {
+        sections.push(section);
+    }
+    sections
+}
+
+fn blocks(reply: Reply) -> Vec<Value> {
+    let mut blocks: Vec<Value> = sections(&reply.text)
+        .into_iter()
+        .take(MAX_BLOCKS)
+        .map(|text| json!({ "type": "section", "text": { "type": "mrkdwn", "text": text } }))
+        .collect();
+    let rows = reply
+        .buttons
+        .into_iter()
+        .flat_map(|row| {
+            row.chunks(MAX_ELEMENTS_PER_ACTIONS)
+                .map(|row| row.to_vec())
+                .collect::<Vec<_>>()
+        })
+        .take(MAX_BLOCKS - blocks.len());
+    for (i, row) in rows.enumerate() {
+        let elements: Vec<Value> = row
+            .into_iter()
+            .enumerate()
+            .map(|(j, Button { label, action })| {
+                let label: String = label.chars().take(MAX_BUTTON_LABEL_LENGTH).collect();
+                let text = json!({ "type": "plain_text", "text": label, "emoji": true });
+                let action_id = format!("action-{}-{}", i, j);
+                match action {
+                    ButtonAction::Callback(value) => {
+                        json!({ "type": "button", "text": text, "action_id": action_id, "value": value })
+                    }
+                    ButtonAction::Url(url) => {
+                        json!({ "type": "button", "text": text, "action_id": action_id, "url": url })
+                    }
+                }
+            })
+            .collect();
+        blocks.push(json!({ "type": "actions", "elements": elements }));
+    }
+    blocks
+}
+
+/// Posts the reply to the `response_url` of a command or interaction.
+async fn respond(response_url: &str, reply: Reply, replace_original: bool) {
+    let body = json!({
+        "response_type": "in_channel",
+        "replace_original": replace_original,
+        "text": reply.text,
+        "blocks": blocks(reply),
+    });
+    match CLIENT.post(response_url).json(&body).send().await {
+        Ok(response) if response.status().is_success() => {}
+        Ok(response) => warn!(status = %response.status(), "Failed to respond to Slack"),
+        Err(err) => warn!(error = %err, "Failed to respond to Slack"),
+    }
+}
+
+fn verify(app: &AppState, request: &HttpRequest, body: &[u8]) -> Result<(), HttpResponse> {
+    let signing_secret = match &app.config.slack.signing_secret {
+        Some(signing_secret) => signing_secret,
+        None => return Err(HttpResponse::NotFound().finish()),
+    };
+    if !verify_signature(signing_secret, request, body) {
+        metrics::UPDATES
+            .with_label_values(&["slack_unauthorized"])
+            .inc();
+        return Err(HttpResponse::Unauthorized().finish());
+    }
+    Ok(())
+}
+
+pub async fn commands(
+    app: web::Data<AppState>,
+    request: HttpRequest,
+    body: web::Bytes,
+) -> HttpResponse {
+    if let Err(response) = verify(&app, &request, &body) {
+        return response;
+    }
+    let command: SlashCommand = match serde_urlencoded::from_bytes(&body) {
+        Ok(command) => command,
+        Err(_) => return HttpResponse::BadRequest().finish(),
+    };
+    metrics::UPDATES.with_label_values(&["slack_command"]).inc();
+
+    let session = session(&command.team_id, &command.channel_id, &command.user_id);
+    actix_web::rt::spawn(async move {
+        // Either one slash command per action (`/search 123`),
+        // or a single app command with a subcommand (`/tracker search 123`).
+        let name = command.command.trim_start_matches('/');
+        let (name, args) = if COMMANDS.contains(&name) {
+            (name, Args::new(&command.text))
+        } else {
+            let subcommand = Command::new(&command.text);
+            (subcommand.label, subcommand.args())
+        };
+        let reply = conversation::handle_command::<SlackMrkdwn>(&app, &session, name, args)
+            .await
+            .unwrap_or_else(|| conversation::help::<SlackMrkdwn>(&session));
+        respond(&command.response_url, reply, false).await;
+    });
+
+    HttpResponse::Ok().finish()
+}
+
+pub async fn interactions(
+    app: web::Data<AppState>,
+    request: HttpRequest,
+    body: web::Bytes,
+) -> HttpResponse {
+    if let Err(response) = verify(&app, &request, &body) {
+        return response;
+    }
+    let payload: BlockActions = match serde_urlencoded::from_bytes::<InteractionForm>(&body)
+        .ok()
+        .and_then(|form| serde_json::from_str(&form.payload).ok())
+    {
+        Some(payload) => payload,
+        None => return HttpResponse::BadRequest().finish(),
+    };
+    metrics::UPDATES
+        .with_label_values(&["slack_interaction"])
+        .inc();
+
+    let (response_url, data) = match (
+        payload.response_url,
+        payload.actions.into_iter().find_map(|action| action.value),
+    ) {
+        (Some(response_url), Some(data)) => (response_url, data),
+        // URL buttons carry no value and need no response.
+        _ => return HttpResponse::Ok().finish(),
+    };
+    // The channel decides whose parcels the buttons act on, so never guess it.
+    let channel_id = match payload.channel {
+        Some(channel) => channel.id,
+        None => return HttpResponse::BadRequest().finish(),
+    };
+    let session = session(&payload.team.id, &channel_id, &payload.user.id);
+    actix_web::rt::spawn(async move {
+        if let Some(reply) = conversation::handle_action::<SlackMrkdwn>(&app, &session, &data).await
+        {
+            respond(&response_url, reply, true).await;
+        }
+    });
+
+    HttpResponse::Ok().finish()
+}
+
+#[cfg(test)]
+mod tests {
+    use actix_web::test::TestRequest;
+
+    use super::*;
+
+    const SECRET: &str = "8f742231b10e8888abcd99yyyzzz85a5";
+    const BODY: &[u8] = b"command=%2Fsearch&text=1234&team_id=T1&channel_id=C1&user_id=U1";
+
+    fn now() -> i64 {
+        SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap()
+            .as_secs() as i64
+    }
+
+    fn signed_request(timestamp: i64, body: &[u8]) -> HttpRequest {
+        let mut mac = Hmac::<Sha256>::new_from_slice(SECRET.as_bytes()).unwrap();
+        mac.update(format!("v0:{}:", timestamp).as_bytes());
+        mac.update(body);
+        let signature = format!("v0={}", hex::encode(mac.finalize().into_bytes()));
+        TestRequest::default()
+            .insert_header((SIGNATURE_HEADER, signature))
+            .insert_header((TIMESTAMP_HEADER, timestamp.to_string()))
+            .to_http_request()
+    }
+
+    #[test]
+    fn accepts_signed_requests() {
+        assert!(verify_signature(SECRET, &signed_request(now(), BODY), BODY));
+    }
+
+    #[test]
+    fn rejects_tampered_or_unsigned_requests() {
+        let request = signed_request(now(), BODY);
+        assert!(!verify_signature(SECRET, &request, b"command=%2Flist"));
+        assert!(!verify_signature("another secret", &request, BODY));
+        assert!(!verify_signature(
+            SECRET,
+            &TestRequest::default().to_http_request(),
+            BODY
+        ));
+    }
+
+    #[test]
+    fn rejects_stale_requests() {
+        let timestamp = now() - MAX_REQUEST_AGE_SECS - 60;
+        assert!(!verify_signature(
+            SECRET,
+            &signed_request(timestamp, BODY),
+            BODY
+        ));
+    }
+
+    #[test]
+    fn splits_long_text_into_sections() {
+        let line = format!("{}\n", "a".repeat(999));
+        let texts: Vec<String> = blocks(Reply::text(line.repeat(4)))
+            .iter()
+            .map(|block| block["text"]["text"].as_str().unwrap().to_string())
+            .collect();
+        assert_eq!(texts, [line.repeat(3), line]);
+
+        let blocks = blocks(Reply::text("b".repeat(MAX_SECTION_TEXT_LENGTH * 2 + 1)));
+        assert_eq!(blocks.len(), 3);
+        for block in &blocks {
+            let text = block["text"]["text"].as_str().unwrap();
+            assert!(text.chars().count() <= MAX_SECTION_TEXT_LENGTH);
+        }
+    }
+
+    #[test]
+    fn blocks_respect_slack_limits() {
+        let buttons = (0..60)
+            .map(|i| {
+                (0..30)
+                    .map(|j| Button::callback("x".repeat(100), format!("{}:{}", i, j)))
+                    .collect()
+            })
+            .collect();
+        let blocks = blocks(Reply::with_buttons("text".to_string(), buttons));
+
+        assert_eq!(blocks.len(), MAX_BLOCKS);
+        for block in &blocks[1..] {
+            let elements = block["elements"].as_array().unwrap();
+            assert!(elements.len() <= MAX_ELEMENTS_PER_ACTIONS);
+            for element in elements {
+                let label = element["text"]["text"].as_str().unwrap();
+                assert_eq!(label.chars().count(), MAX_BUTTON_LABEL_LENGTH);
+            }
+        }
+    }
+}
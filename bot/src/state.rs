@@ -0,0 +1,121 @@
+use bot::tracker::tracker_client::TrackerClient;
+use telbot_hyper::Api;
+use tonic::{
+    codegen::InterceptedService,
+    metadata::{Ascii, MetadataValue},
+    service::Interceptor,
+    transport::{Certificate, Channel, ClientTlsConfig, Endpoint, Identity},
+    Request, Status,
+};
+
+use crate::{
+    config::{Config, ConfigError, GrpcTlsConfig},
+    metrics::MeteredApi,
+    request_id::{REQUEST_ID, REQUEST_ID_HEADER},
+    settings::SettingsStore,
+};
+
+const AUTHORIZATION_HEADER: &str = "authorization";
+
+#[derive(Clone)]
+pub struct TrackerInterceptor {
+    api_key: Option<MetadataValue<Ascii>>,
+}
+
+impl Interceptor for TrackerInterceptor {
+    fn call(&mut self, mut request: Request<()>) -> Result<Request<()>, Status> {
+        if let Some(api_key) = &self.api_key {
+            request
+                .metadata_mut()
+                .insert(AUTHORIZATION_HEADER, api_key.clone());
+        }
+        if let Ok(Ok(value)) = REQUEST_ID.try_with(|request_id| MetadataValue::from_str(request_id))
+        {
+            request.metadata_mut().insert(REQUEST_ID_HEADER, value);
+        }
+        Ok(request)
+    }
+}
+
+fn client_tls_config(config: &GrpcTlsConfig) -> std::io::Result<ClientTlsConfig> {
+    let mut tls_config = ClientTlsConfig::new();
+    if let Some(ca_path) = &config.ca_path {
+        let ca = std::fs::read(ca_path)?;
+        tls_config = tls_config.ca_certificate(Certificate::from_pem(ca));
+    }
+    if let (Some(cert_path), Some(key_path)) = (&config.cert_path, &config.key_path) {
+        let cert = std::fs::read(cert_path)?;
+        let key = std::fs::read(key_path)?;
+        tls_config = tls_config.identity(Identity::from_pem(cert, key));
+    }
+    if let Some(domain) = &config.domain {
+        tls_config = tls_config.domain_name(domain.clone());
+    }
+
+    Ok(tls_config)
+}
+
+pub struct AppState {
+    pub api: MeteredApi,
+    pub config: Config,
+    pub settings: SettingsStore,
+    tracker: TrackerClient<InterceptedService<Channel, TrackerInterceptor>>,
+}
+
+impl AppState {
+    pub fn new(config: Config) -> Result<Self, ConfigError> {
+        let api = MeteredApi::new(Api::new(config.bot_token.clone()));
+        let mut endpoint = Endpoint::from_shared(config.grpc.addr.clone())
+            .map_err(|_| ConfigError::Invalid("grpc.addr is not a valid uri".to_string()))?
+            .connect_timeout(config.grpc.connect_timeout())
+            .timeout(config.grpc.request_timeout());
+        if config.grpc.tls.is_enabled() {
+            let tls_config = client_tls_config(&config.grpc.tls).map_err(|err| {
+                ConfigError::Invalid(format!("can't read gRPC TLS certificates: {}", err))
+            })?;
+            endpoint = endpoint.tls_config(tls_config).map_err(|err| {
+                ConfigError::Invalid(format!("invalid gRPC TLS configuration: {}", err))
+            })?;
+        }
+        let channel = endpoint
+            .connect_lazy()
+            .map_err(|err| ConfigError::Invalid(format!("can't create gRPC channel: {}", err)))?;
+        let api_key = match &config.grpc.api_key {
+            Some(api_key) => Some(
+                MetadataValue::from_str(&format!("Bearer {}", api_key)).map_err(|_| {
+                    ConfigError::Invalid("grpc.api_key is not a valid header value".to_string())
+                })?,
+            ),
+            None => None,
+        };
+        let interceptor = TrackerInterceptor { api_key };
+        let settings = match &config.settings_path {
+            Some(path) => SettingsStore::open(path).map_err(|err| {
+                ConfigError::Invalid(format!("can't open settings store: {}", err))
+            })?,
+            None => SettingsStore::default(),
+        };
+
+        Ok(Self {
+            api,
+            config,
+            settings,
+            tracker: TrackerClient::with_interceptor(channel, interceptor),
+        })
+    }
+
+    pub fn is_admin(&self, user_id: i64) -> bool {
+        self.config.admin_ids.contains(&user_id)
+    }
+
+    pub fn file_url(&self, file_path: &str) -> String {
+        format!(
+            "https://api.telegram.org/file/bot{}/{}",
+            self.config.bot_token, file_path
+        )
+    }
+
+    pub fn tracker(&self) -> TrackerClient<InterceptedService<Channel, TrackerInterceptor>> {
+        self.tracker.clone()
+    }
+}
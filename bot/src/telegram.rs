@@ -1,12 +1,73 @@
-use bot::tracker::{
-    SearchResponse, StatusKind, SupportCouriersResponse, TrackingDetail, TrackingInfo,
+use bot::tracker::{SearchResponse, TrackingInfo};
+use once_cell::sync::OnceCell;
+use serde::Serialize;
+use telbot_hyper::types::{
+    chat::ChatKind,
+    markup::{InlineKeyboardButton, InlineKeyboardButtonKind, InlineKeyboardMarkup, ParseMode},
+    message::{EditMessageText, Message, SendMessage},
+    JsonMethod, TelegramMethod,
 };
-use chrono::TimeZone;
-use chrono_tz::Asia::Seoul;
-use telbot_hyper::types::markup::{
-    InlineKeyboardButton, InlineKeyboardButtonKind, InlineKeyboardMarkup,
+use tracing::warn;
+
+use crate::{
+    conversation::Session,
+    locale::Locale,
+    render::{self, Button, ButtonAction, Markup, Reply},
+    settings::UserSettings,
+    state::AppState,
 };
 
+pub static BOT_USERNAME: OnceCell<String> = OnceCell::new();
+
+pub struct MarkdownV2;
+
+/// `editMessageText` for messages sent through inline mode, which have no chat to address.
+#[derive(Serialize)]
+struct EditInlineMessageText {
+    inline_message_id: String,
+    text: String,
+    parse_mode: ParseMode,
+    reply_markup: InlineKeyboardMarkup,
+}
+
+impl TelegramMethod for EditInlineMessageText {
+    type Response = bool;
+
+    fn name() -> &'static str {
+        "editMessageText"
+    }
+}
+
+impl JsonMethod for EditInlineMessageText {}
+
+impl Markup for MarkdownV2 {
+    const MESSAGE_LIMIT: usize = 4000;
+
+    fn escape(text: &str) -> String {
+        escape(text)
+    }
+
+    fn bold(text: &str) -> String {
+        format!("*{}*", escape(text))
+    }
+}
+
+pub fn sender_id(message: &Message) -> i64 {
+    message
+        .from
+        .as_ref()
+        .map(|user| user.id)
+        .unwrap_or(message.chat.id)
+}
+
+pub fn tracker_user_id(chat_id: i64) -> String {
+    format!("telegram:{}", chat_id)
+}
+
+pub fn is_private_chat(message: &Message) -> bool {
+    matches!(message.chat.kind, ChatKind::Private { .. })
+}
+
 pub fn escape<S>(input: S) -> String
 where
     S: Into<String>,
@@ -24,138 +85,86 @@ where
     output
 }
 
-fn create_info_header_message(info: &TrackingInfo) -> String {
-    format!(
-        "📦 *{name}* {tracking_number}\n\
-        {sender} ▶️ {receiver}{product}",
-        name = info.name,
-        tracking_number = info.tracking_number,
-        sender = escape(info.sender.as_ref().unwrap_or(&"정보 없음".to_string())),
-        receiver = escape(info.receiver.as_ref().unwrap_or(&"정보 없음".to_string())),
-        product = if let Some(product) = &info.product {
-            escape(format!(" ({})", product))
-        } else {
-            "".to_string()
-        }
-    )
+fn keyboard(buttons: Vec<Vec<Button>>) -> InlineKeyboardMarkup {
+    let inline_keyboard = buttons
+        .into_iter()
+        .map(|row| {
+            row.into_iter()
+                .map(|button| InlineKeyboardButton {
+                    text: button.label,
+                    kind: match button.action {
+                        ButtonAction::Callback(callback_data) => {
+                            InlineKeyboardButtonKind::Callback { callback_data }
+                        }
+                        ButtonAction::Url(url) => InlineKeyboardButtonKind::Url { url },
+                    },
+                })
+                .collect()
+        })
+        .collect();
+
+    InlineKeyboardMarkup { inline_keyboard }
 }
 
-fn create_detail_message(detail: &TrackingDetail) -> String {
-    let datetime = Seoul
-        .datetime_from_str(&detail.time, "%Y-%m-%d %H:%M:%S")
-        .unwrap();
-
-    if detail.message.is_some() && detail.status.is_some() && detail.location.is_some() {
-        escape(format!(
-            "{} ({}, {}): {}",
-            detail.status(),
-            datetime.format("%H시 %M분"),
-            detail.location(),
-            detail.message(),
-        ))
-    } else if detail.message.is_none() && detail.status.is_some() && detail.location.is_some() {
-        escape(format!(
-            "{}: [{}] {}",
-            datetime.format("%H시 %M분"),
-            detail.location(),
-            detail.status(),
-        ))
-    } else if detail.message.is_some() && detail.status.is_none() && detail.location.is_some() {
-        escape(format!(
-            "{}: [{}] {}",
-            datetime.format("%H시 %M분"),
-            detail.location(),
-            detail.message(),
-        ))
-    } else if detail.message.is_some() && detail.status.is_none() && detail.location.is_none() {
-        escape(format!(
-            "{}: {}",
-            datetime.format("%H시 %M분"),
-            detail.message(),
-        ))
-    } else {
-        String::new()
+/// Builds the conversation session for a Telegram chat member.
+pub fn session(app: &AppState, chat_id: i64, user_id: i64, is_chat_admin: bool) -> Session {
+    Session {
+        user_id: tracker_user_id(chat_id),
+        requested_by: user_id.to_string(),
+        is_chat_admin,
+        settings: app.settings.get(user_id),
+        dialogue: true,
     }
 }
 
-pub fn create_simple_tracking_message(response: &SearchResponse) -> String {
-    match response.status() {
-        StatusKind::Ok => {
-            let info = response.tracking_info.as_ref().unwrap();
-            let header = create_info_header_message(&info);
-            if info.tracks.len() == 0 {
-                return header;
-            }
-            let last_detail = info.tracks.last().unwrap();
-
-            let datetime = Seoul
-                .datetime_from_str(&last_detail.time, "%Y-%m-%d %H:%M:%S")
-                .unwrap();
-
-            let detail_message = create_detail_message(last_detail);
-
-            format!(
-                "{}\n\
-                \n\
-                *{}*\n\
-                {}",
-                header,
-                datetime.format("%Y년 %m월 %d일"),
-                detail_message
-            )
-        }
-        StatusKind::RequestFailed => String::new(),
-        StatusKind::WrongTrackingNumber => String::new(),
-        StatusKind::NotExistsTrackingNumber => String::new(),
-        StatusKind::TrackingAlreadyExists => todo!(),
-        StatusKind::TrackingNotExists => todo!(),
+/// Sends the reply as a new message in the chat.
+pub async fn send_reply(app: &AppState, chat_id: i64, reply: Reply) {
+    let mut send_message =
+        SendMessage::new(chat_id, reply.text).with_parse_mode(ParseMode::MarkdownV2);
+    if !reply.buttons.is_empty() {
+        send_message = send_message.with_reply_markup(keyboard(reply.buttons));
+    }
+    if let Err(err) = app.api.send_json(&send_message).await {
+        warn!("Failed to send message: {:?}", err);
     }
 }
 
-pub fn create_search_result_keyboard(url: String, can_track: bool, tracking_number: String) -> InlineKeyboardMarkup {
-    let mut rows = Vec::new();
-
-    let mut row = Vec::new();
-    row.push(InlineKeyboardButton {
-        text: "📄 모든 처리 정보 표시".to_string(),
-        kind: InlineKeyboardButtonKind::Callback { callback_data: format!("show_all:{}", tracking_number) },
-    });
-    if can_track {
-        row.push(InlineKeyboardButton {
-            text: "🔍 운송장 추적".to_string(),
-            kind: InlineKeyboardButtonKind::Callback { callback_data: format!("track:{}", tracking_number) },
-        });
+/// Replaces the text and buttons of a message the bot sent before.
+pub async fn edit_reply(app: &AppState, message: &Message, reply: Reply) {
+    let edit_message_text = EditMessageText::new(message.chat.id, message.message_id, reply.text)
+        .with_parse_mode(ParseMode::MarkdownV2)
+        .with_reply_markup(keyboard(reply.buttons));
+    if let Err(err) = app.api.send_json(&edit_message_text).await {
+        warn!("Failed to edit message: {:?}", err);
     }
-    rows.push(row);
-
-    rows.push(vec![InlineKeyboardButton {
-        text: "🔗 홈페이지에서 보기".to_string(),
-        kind: InlineKeyboardButtonKind::Url { url },
-    }]);
+}
 
-    InlineKeyboardMarkup {
-        inline_keyboard: rows,
+/// Replaces an inline mode message with the reply.
+pub async fn edit_inline_reply(app: &AppState, inline_message_id: &str, reply: Reply) {
+    let edit_message_text = EditInlineMessageText {
+        inline_message_id: inline_message_id.to_string(),
+        text: reply.text,
+        parse_mode: ParseMode::MarkdownV2,
+        reply_markup: keyboard(reply.buttons),
+    };
+    if let Err(err) = app.api.send_json(&edit_message_text).await {
+        warn!("Failed to edit inline message: {:?}", err);
     }
 }
 
-pub fn create_courier_keyboard(support_couriers: SupportCouriersResponse) -> InlineKeyboardMarkup {
-    let rows = support_couriers
-        .couriers
-        .iter()
-        .map(|courier| InlineKeyboardButton {
-            text: courier.name.clone(),
-            kind: InlineKeyboardButtonKind::Callback {
-                callback_data: courier.id.clone(),
-            },
-        })
-        .collect::<Vec<InlineKeyboardButton>>()
-        .chunks(2)
-        .collect::<Vec<&[InlineKeyboardButton]>>()
-        .iter()
-        .map(|vec| vec.to_vec())
-        .collect::<Vec<Vec<InlineKeyboardButton>>>();
-
-    InlineKeyboardMarkup {
-        inline_keyboard: rows,
-    }
+pub fn create_simple_tracking_message(
+    response: &SearchResponse,
+    settings: &UserSettings,
+) -> String {
+    render::create_simple_tracking_message::<MarkdownV2>(response, settings)
+}
+
+pub fn create_search_result_keyboard(
+    info: &TrackingInfo,
+    can_track: bool,
+    locale: Locale,
+) -> InlineKeyboardMarkup {
+    keyboard(render::create_search_result_buttons(
+        info, can_track, locale,
+    ))
 }
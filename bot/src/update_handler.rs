@@ -0,0 +1,100 @@
+use actix_web::web;
+use telbot_hyper::types::{
+    message::MessageKind,
+    update::{Update, UpdateKind},
+    user::User,
+};
+use tracing::{info, info_span, warn, Instrument};
+
+use crate::{
+    barcode, callback_handler, command_handler, conversation,
+    dialogue::Dialogue,
+    inline_handler, metrics,
+    request_id::{self, REQUEST_ID},
+    state::AppState,
+    telegram::{self, MarkdownV2},
+};
+
+fn sender(update: &Update) -> Option<&User> {
+    match &update.kind {
+        UpdateKind::Message { message } => message.from.as_ref(),
+        UpdateKind::CallbackQuery { callback_query } => Some(&callback_query.from),
+        UpdateKind::InlineQuery { inline_query } => Some(&inline_query.from),
+        _ => None,
+    }
+}
+
+fn kind(update: &Update) -> &'static str {
+    match &update.kind {
+        UpdateKind::Message { .. } => "message",
+        UpdateKind::CallbackQuery { .. } => "callback_query",
+        UpdateKind::InlineQuery { .. } => "inline_query",
+        _ => "other",
+    }
+}
+
+pub async fn handle_update(app: &web::Data<AppState>, update: &Update) {
+    let request_id = request_id::for_update(update.update_id);
+    let span = info_span!(
+        "update",
+        request_id = %request_id,
+        update_id = update.update_id,
+        kind = kind(update),
+    );
+
+    REQUEST_ID
+        .scope(request_id, handle(app, update).instrument(span))
+        .await;
+}
+
+async fn handle(app: &web::Data<AppState>, update: &Update) {
+    metrics::UPDATES.with_label_values(&[kind(update)]).inc();
+    info!("Handling update");
+
+    if let Some(user) = sender(update) {
+        app.settings
+            .update(user.id, |settings| {
+                settings.language_code = user.language_code.clone()
+            })
+            .ok();
+        if app.settings.get(user.id).banned {
+            return;
+        }
+    }
+
+    if let UpdateKind::Message { message } = &update.kind {
+        let chat_id = message.chat.id;
+        let session = telegram::session(app, chat_id, telegram::sender_id(message), false);
+        if let Some(text) = message.kind.text() {
+            if text.starts_with('/') {
+                command_handler::handle_command(app, message, text).await;
+            } else if let Some(reply) =
+                conversation::handle_message::<MarkdownV2>(app, &session, text).await
+            {
+                telegram::send_reply(app, chat_id, reply).await;
+            } else if telegram::is_private_chat(message) {
+                if let Err(err) = app.api.send_json(&message.reply_text(text)).await {
+                    warn!("Failed to send message: {:?}", err);
+                }
+            }
+        } else if let MessageKind::Photo { photo, caption, .. } = &message.kind {
+            // Only a pending prompt is worth downloading the photo for.
+            if Dialogue::get(&session.user_id, &session.requested_by).is_some() {
+                let mut text = barcode::read_photo(app, photo).await.join("\n");
+                if let Some(caption) = caption {
+                    text.push('\n');
+                    text.push_str(caption);
+                }
+                if let Some(reply) =
+                    conversation::handle_message::<MarkdownV2>(app, &session, &text).await
+                {
+                    telegram::send_reply(app, chat_id, reply).await;
+                }
+            }
+        }
+    } else if let UpdateKind::CallbackQuery { callback_query } = &update.kind {
+        callback_handler::handle_callback_query(app, callback_query).await;
+    } else if let UpdateKind::InlineQuery { inline_query } = &update.kind {
+        inline_handler::handle_inline_query(app, inline_query).await;
+    }
+}
@@ -0,0 +1,163 @@
+use std::{
+    collections::{HashSet, VecDeque},
+    sync::Mutex,
+};
+
+use once_cell::sync::Lazy;
+use serde::Serialize;
+use telbot_hyper::types::{JsonMethod, TelegramMethod};
+
+use crate::{config::WebhookConfig, metrics::MeteredApi};
+
+pub const SECRET_TOKEN_HEADER: &str = "X-Telegram-Bot-Api-Secret-Token";
+const RECENT_UPDATES_CAPACITY: usize = 1024;
+
+static RECENT_UPDATES: Lazy<Mutex<RecentUpdates>> =
+    Lazy::new(|| Mutex::new(RecentUpdates::with_capacity(RECENT_UPDATES_CAPACITY)));
+
+#[derive(Serialize)]
+struct SetWebhook {
+    url: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    secret_token: Option<String>,
+}
+
+impl TelegramMethod for SetWebhook {
+    type Response = bool;
+
+    fn name() -> &'static str {
+        "setWebhook"
+    }
+}
+
+impl JsonMethod for SetWebhook {}
+
+struct RecentUpdates {
+    capacity: usize,
+    order: VecDeque<u32>,
+    ids: HashSet<u32>,
+}
+
+impl RecentUpdates {
+    fn with_capacity(capacity: usize) -> Self {
+        Self {
+            capacity,
+            order: VecDeque::with_capacity(capacity),
+            ids: HashSet::with_capacity(capacity),
+        }
+    }
+
+    fn insert(&mut self, update_id: u32) -> bool {
+        if !self.ids.insert(update_id) {
+            return false;
+        }
+        self.order.push_back(update_id);
+        if self.order.len() > self.capacity {
+            if let Some(oldest) = self.order.pop_front() {
+                self.ids.remove(&oldest);
+            }
+        }
+        true
+    }
+
+    fn remove(&mut self, update_id: u32) {
+        if self.ids.remove(&update_id) {
+            self.order.retain(|id| *id != update_id);
+        }
+    }
+}
+
+/// An update being handled. Unless it's completed, dropping it forgets the update id again,
+/// so Telegram can redeliver an update whose handling panicked or was cancelled.
+pub struct UpdateClaim {
+    update_id: u32,
+    completed: bool,
+}
+
+impl UpdateClaim {
+    pub fn complete(mut self) {
+        self.completed = true;
+    }
+}
+
+impl Drop for UpdateClaim {
+    fn drop(&mut self) {
+        if !self.completed {
+            if let Ok(mut updates) = RECENT_UPDATES.lock() {
+                updates.remove(self.update_id);
+            }
+        }
+    }
+}
+
+pub fn verify_secret(secret: Option<&str>, token: Option<&str>) -> bool {
+    match (secret, token) {
+        (None, _) => true,
+        (Some(secret), Some(token)) => {
+            secret.len() == token.len()
+                && secret
+                    .bytes()
+                    .zip(token.bytes())
+                    .fold(0, |acc, (a, b)| acc | (a ^ b))
+                    == 0
+        }
+        (Some(_), None) => false,
+    }
+}
+
+/// Claims an update for handling, or returns `None` if it was handled or is being handled.
+pub fn claim_update(update_id: u32) -> Option<UpdateClaim> {
+    let is_new = RECENT_UPDATES
+        .lock()
+        .map(|mut updates| updates.insert(update_id))
+        .unwrap_or(true);
+    is_new.then(|| UpdateClaim {
+        update_id,
+        completed: false,
+    })
+}
+
+pub async fn set_webhook(api: &MeteredApi, url: &str, config: &WebhookConfig) {
+    let set_webhook = SetWebhook {
+        url: format!("{}{}", url.trim_end_matches('/'), config.path),
+        secret_token: config.secret.clone(),
+    };
+    api.send_json(&set_webhook)
+        .await
+        .expect("Failed to set webhook");
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn forgets_the_oldest_updates() {
+        let mut updates = RecentUpdates::with_capacity(2);
+        assert!(updates.insert(1));
+        assert!(!updates.insert(1));
+        assert!(updates.insert(2));
+        assert!(updates.insert(3));
+        assert!(updates.insert(1));
+        assert!(!updates.insert(3));
+    }
+
+    #[test]
+    fn redelivers_unfinished_updates() {
+        let update_id = u32::MAX;
+        let claim = claim_update(update_id).unwrap();
+        assert!(claim_update(update_id).is_none());
+        drop(claim);
+
+        claim_update(update_id).unwrap().complete();
+        assert!(claim_update(update_id).is_none());
+    }
+
+    #[test]
+    fn verifies_secret_token() {
+        assert!(verify_secret(None, None));
+        assert!(verify_secret(Some("secret"), Some("secret")));
+        assert!(!verify_secret(Some("secret"), Some("secreT")));
+        assert!(!verify_secret(Some("secret"), None));
+    }
+}
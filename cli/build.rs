@@ -0,0 +1,19 @@
+fn main() -> Result<(), Box<dyn std::error::Error>> {
+    tonic_build::configure()
+        .build_server(false)
+        .type_attribute(".tracker", "#[derive(serde::Serialize)]")
+        .field_attribute(
+            "tracker.SearchResponse.status",
+            "#[serde(serialize_with = \"crate::output::serialize_status\")]",
+        )
+        .field_attribute(
+            "tracker.AddTrackResponse.status",
+            "#[serde(serialize_with = \"crate::output::serialize_status\")]",
+        )
+        .field_attribute(
+            "tracker.RemoveTrackResponse.status",
+            "#[serde(serialize_with = \"crate::output::serialize_status\")]",
+        )
+        .compile(&["../proto/tracker.proto"], &["../proto/"])?;
+    Ok(())
+}
@@ -0,0 +1,373 @@
+#![warn(clippy::all)]
+
+use std::path::PathBuf;
+
+use clap::{Parser, Subcommand};
+use output::{print_list, print_table, print_value, status_name, Format};
+use tonic::{
+    codegen::InterceptedService,
+    metadata::{Ascii, MetadataValue},
+    service::Interceptor,
+    transport::{Certificate, Channel, ClientTlsConfig, Endpoint, Identity},
+    Request, Status,
+};
+use tracker::{
+    tracker_client::TrackerClient, AddTrackRequest, CallbackDeliveriesRequest, RemoveTrackRequest,
+    SearchRequest, StatusKind, SupportCouriersRequest, TrackingInfo, TrackingListRequest,
+};
+
+mod output;
+
+pub mod tracker {
+    tonic::include_proto!("tracker");
+}
+
+type Client = TrackerClient<InterceptedService<Channel, ApiKeyInterceptor>>;
+
+#[derive(Parser)]
+#[clap(name = "tracker", about = "Query the delivery tracker server")]
+struct Opts {
+    /// Tracker server address
+    #[clap(long, env = "GRPC_ADDR", default_value = "http://localhost:50051")]
+    addr: String,
+    /// API key sent as a bearer token
+    #[clap(long, env = "GRPC_API_KEY", hide_env_values = true)]
+    api_key: Option<String>,
+    /// CA certificate used to verify an https:// server
+    #[clap(long, env = "GRPC_TLS_CA_PATH")]
+    tls_ca: Option<PathBuf>,
+    /// Domain name expected in the server certificate
+    #[clap(long, env = "GRPC_TLS_DOMAIN")]
+    tls_domain: Option<String>,
+    /// Client certificate for servers that require one, used with --tls-key
+    #[clap(long, env = "GRPC_TLS_CERT_PATH")]
+    tls_cert: Option<PathBuf>,
+    /// Private key of the client certificate
+    #[clap(long, env = "GRPC_TLS_KEY_PATH")]
+    tls_key: Option<PathBuf>,
+    #[clap(short, long, arg_enum, default_value = "table")]
+    output: Format,
+    #[clap(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// List couriers that accept the tracking number
+    Couriers { tracking_number: String },
+    /// Look up a tracking number
+    Search {
+        courier_id: String,
+        tracking_number: String,
+    },
+    /// Manage tracked numbers
+    #[clap(subcommand)]
+    Track(TrackCommand),
+}
+
+#[derive(Subcommand)]
+enum TrackCommand {
+    /// Start tracking a number
+    Add {
+        /// Namespaced user id, e.g. cli:alice
+        #[clap(long, env = "TRACKER_USER_ID")]
+        user: String,
+        courier_id: String,
+        tracking_number: String,
+        #[clap(long)]
+        label: Option<String>,
+        #[clap(long)]
+        note: Option<String>,
+        #[clap(long = "tag")]
+        tags: Vec<String>,
+        /// URL that receives signed POSTs when new tracking events appear
+        #[clap(long)]
+        callback_url: Option<String>,
+        /// Address that receives an email when new tracking events appear
+        #[clap(long)]
+        email: Option<String>,
+    },
+    /// Stop tracking a number
+    Remove {
+        #[clap(long, env = "TRACKER_USER_ID")]
+        user: String,
+        courier_id: String,
+        tracking_number: String,
+        /// Remove even if someone else added it, needs an API key with trust_chat_admin
+        #[clap(long)]
+        force: bool,
+    },
+    /// List tracked numbers
+    List {
+        #[clap(long, env = "TRACKER_USER_ID")]
+        user: String,
+    },
+    /// Show recent callback delivery attempts
+    Deliveries {
+        #[clap(long, env = "TRACKER_USER_ID")]
+        user: String,
+        courier_id: String,
+        tracking_number: String,
+    },
+}
+
+#[derive(Clone)]
+struct ApiKeyInterceptor {
+    api_key: Option<MetadataValue<Ascii>>,
+}
+
+impl Interceptor for ApiKeyInterceptor {
+    fn call(&mut self, mut request: Request<()>) -> Result<Request<()>, Status> {
+        if let Some(api_key) = &self.api_key {
+            request
+                .metadata_mut()
+                .insert("authorization", api_key.clone());
+        }
+        Ok(request)
+    }
+}
+
+fn exit_with_error(context: &str, err: impl std::fmt::Display) -> ! {
+    eprintln!("{}: {}", context, err);
+    std::process::exit(1)
+}
+
+async fn connect(opts: &Opts) -> Client {
+    let mut endpoint = Endpoint::from_shared(opts.addr.clone())
+        .unwrap_or_else(|err| exit_with_error("Invalid server address", err));
+    if opts.tls_cert.is_some() != opts.tls_key.is_some() {
+        exit_with_error(
+            "Invalid TLS configuration",
+            "--tls-cert and --tls-key must be set together",
+        );
+    }
+    if opts.tls_ca.is_some() || opts.tls_domain.is_some() || opts.tls_cert.is_some() {
+        let mut tls_config = ClientTlsConfig::new();
+        if let Some(ca_path) = &opts.tls_ca {
+            let ca = std::fs::read(ca_path)
+                .unwrap_or_else(|err| exit_with_error("Can't read CA certificate", err));
+            tls_config = tls_config.ca_certificate(Certificate::from_pem(ca));
+        }
+        if let (Some(cert_path), Some(key_path)) = (&opts.tls_cert, &opts.tls_key) {
+            let cert = std::fs::read(cert_path)
+                .unwrap_or_else(|err| exit_with_error("Can't read client certificate", err));
+            let key = std::fs::read(key_path)
+                .unwrap_or_else(|err| exit_with_error("Can't read client key", err));
+            tls_config = tls_config.identity(Identity::from_pem(cert, key));
+        }
+        if let Some(domain) = &opts.tls_domain {
+            tls_config = tls_config.domain_name(domain.clone());
+        }
+        endpoint = endpoint
+            .tls_config(tls_config)
+            .unwrap_or_else(|err| exit_with_error("Invalid TLS configuration", err));
+    }
+    let channel = endpoint
+        .connect()
+        .await
+        .unwrap_or_else(|err| exit_with_error("Can't connect to tracker server", err));
+
+    let api_key = opts.api_key.as_ref().map(|api_key| {
+        MetadataValue::from_str(&format!("Bearer {}", api_key))
+            .unwrap_or_else(|err| exit_with_error("Invalid API key", err))
+    });
+    TrackerClient::with_interceptor(channel, ApiKeyInterceptor { api_key })
+}
+
+/// Exits with 2 on a failed status; call it after printing so JSON output is still written.
+fn check_status(status: i32, message: Option<&str>) {
+    if status != StatusKind::Ok as i32 {
+        match message.filter(|message| !message.is_empty()) {
+            Some(message) => eprintln!("{}: {}", status_name(status), message),
+            None => eprintln!("{}", status_name(status)),
+        }
+        std::process::exit(2);
+    }
+}
+
+fn print_tracking_info(info: &TrackingInfo) {
+    println!("{} {}", info.name, info.tracking_number);
+    if let Some(sender) = &info.sender {
+        println!("Sender: {}", sender);
+    }
+    if let Some(receiver) = &info.receiver {
+        println!("Receiver: {}", receiver);
+    }
+    if let Some(product) = &info.product {
+        println!("Product: {}", product);
+    }
+    println!(
+        "Delivered: {}",
+        if info.is_delivered { "yes" } else { "no" }
+    );
+    println!();
+
+    let rows: Vec<Vec<String>> = info
+        .tracks
+        .iter()
+        .map(|track| {
+            vec![
+                track.time.clone(),
+                track.status.clone().unwrap_or_default(),
+                track.location.clone().unwrap_or_default(),
+                track.message.clone().unwrap_or_default(),
+            ]
+        })
+        .collect();
+    print_table(&["TIME", "STATUS", "LOCATION", "MESSAGE"], &rows);
+}
+
+#[tokio::main]
+async fn main() {
+    let opts = Opts::parse();
+    let mut client = connect(&opts).await;
+
+    match opts.command {
+        Command::Couriers { tracking_number } => {
+            let response = client
+                .get_support_couriers(SupportCouriersRequest { tracking_number })
+                .await
+                .unwrap_or_else(|status| exit_with_error("Request failed", status))
+                .into_inner();
+            print_list(
+                opts.output,
+                &response.couriers,
+                &["ID", "NAME"],
+                |courier| vec![courier.id.clone(), courier.name.clone()],
+            );
+        }
+        Command::Search {
+            courier_id,
+            tracking_number,
+        } => {
+            let response = client
+                .search(SearchRequest {
+                    courier_id,
+                    tracking_number,
+                })
+                .await
+                .unwrap_or_else(|status| exit_with_error("Request failed", status))
+                .into_inner();
+            print_value(opts.output, &response, |response| {
+                if let Some(info) = &response.tracking_info {
+                    print_tracking_info(info);
+                }
+            });
+            check_status(response.status, response.message.as_deref());
+        }
+        Command::Track(TrackCommand::Add {
+            user,
+            courier_id,
+            tracking_number,
+            label,
+            note,
+            tags,
+            callback_url,
+            email,
+        }) => {
+            let response = client
+                .add_track(AddTrackRequest {
+                    added_by: user.clone(),
+                    user_id: user,
+                    courier_id,
+                    tracking_number,
+                    label,
+                    note,
+                    tags,
+                    callback_url,
+                    email,
+                })
+                .await
+                .unwrap_or_else(|status| exit_with_error("Request failed", status))
+                .into_inner();
+            print_value(opts.output, &response, |response| {
+                if response.status == StatusKind::Ok as i32 {
+                    println!("Added");
+                }
+            });
+            let message = response
+                .response
+                .as_ref()
+                .and_then(|response| response.message.as_deref());
+            check_status(response.status, message);
+        }
+        Command::Track(TrackCommand::Remove {
+            user,
+            courier_id,
+            tracking_number,
+            force,
+        }) => {
+            let response = client
+                .remove_track(RemoveTrackRequest {
+                    requested_by: user.clone(),
+                    user_id: user,
+                    courier_id,
+                    tracking_number,
+                    is_chat_admin: force,
+                })
+                .await
+                .unwrap_or_else(|status| exit_with_error("Request failed", status))
+                .into_inner();
+            print_value(opts.output, &response, |response| {
+                if response.status == StatusKind::Ok as i32 {
+                    println!("Removed");
+                }
+            });
+            check_status(response.status, None);
+        }
+        Command::Track(TrackCommand::List { user }) => {
+            let response = client
+                .tracking_list(TrackingListRequest { user_id: user })
+                .await
+                .unwrap_or_else(|status| exit_with_error("Request failed", status))
+                .into_inner();
+            print_list(
+                opts.output,
+                &response.entries,
+                &["COURIER", "NUMBER", "DELIVERED", "LABEL", "TAGS"],
+                |entry| {
+                    let info = entry.tracking_info.clone().unwrap_or_default();
+                    vec![
+                        info.id,
+                        info.tracking_number,
+                        if info.is_delivered { "yes" } else { "no" }.to_string(),
+                        entry.label.clone().unwrap_or_default(),
+                        entry.tags.join(","),
+                    ]
+                },
+            );
+        }
+        Command::Track(TrackCommand::Deliveries {
+            user,
+            courier_id,
+            tracking_number,
+        }) => {
+            let response = client
+                .list_callback_deliveries(CallbackDeliveriesRequest {
+                    user_id: user,
+                    courier_id,
+                    tracking_number,
+                })
+                .await
+                .unwrap_or_else(|status| exit_with_error("Request failed", status))
+                .into_inner();
+            print_list(
+                opts.output,
+                &response.deliveries,
+                &["ATTEMPTED_AT", "ATTEMPT", "URL", "STATUS", "ERROR"],
+                |delivery| {
+                    vec![
+                        delivery.attempted_at.to_string(),
+                        delivery.attempt.to_string(),
+                        delivery.url.clone(),
+                        delivery
+                            .status_code
+                            .map(|code| code.to_string())
+                            .unwrap_or_default(),
+                        delivery.error.clone().unwrap_or_default(),
+                    ]
+                },
+            );
+        }
+    }
+}
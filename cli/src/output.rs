@@ -0,0 +1,85 @@
+use clap::ArgEnum;
+use serde::{Serialize, Serializer};
+
+use crate::tracker::StatusKind;
+
+#[derive(ArgEnum, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Format {
+    Table,
+    Json,
+    Ndjson,
+}
+
+pub fn serialize_status<S: Serializer>(status: &i32, serializer: S) -> Result<S::Ok, S::Error> {
+    serializer.serialize_str(status_name(*status))
+}
+
+pub fn status_name(status: i32) -> &'static str {
+    match StatusKind::from_i32(status) {
+        Some(StatusKind::Ok) => "OK",
+        Some(StatusKind::RequestFailed) => "REQUEST_FAILED",
+        Some(StatusKind::WrongTrackingNumber) => "WRONG_TRACKING_NUMBER",
+        Some(StatusKind::NotExistsTrackingNumber) => "NOT_EXISTS_TRACKING_NUMBER",
+        Some(StatusKind::TrackingAlreadyExists) => "TRACKING_ALREADY_EXISTS",
+        Some(StatusKind::TrackingNotExists) => "TRACKING_NOT_EXISTS",
+        Some(StatusKind::PermissionDenied) => "PERMISSION_DENIED",
+        None => "UNKNOWN",
+    }
+}
+
+/// Prints a single value as JSON, or hands it to `table` for human readable output.
+pub fn print_value<T: Serialize>(format: Format, value: &T, table: impl FnOnce(&T)) {
+    match format {
+        Format::Json => println!("{}", serde_json::to_string_pretty(value).unwrap()),
+        Format::Ndjson => println!("{}", serde_json::to_string(value).unwrap()),
+        Format::Table => table(value),
+    }
+}
+
+/// Prints a list as a JSON array, one JSON object per line, or a table.
+pub fn print_list<T: Serialize>(
+    format: Format,
+    items: &[T],
+    headers: &[&str],
+    row: impl Fn(&T) -> Vec<String>,
+) {
+    match format {
+        Format::Json => println!("{}", serde_json::to_string_pretty(items).unwrap()),
+        Format::Ndjson => {
+            for item in items {
+                println!("{}", serde_json::to_string(item).unwrap());
+            }
+        }
+        Format::Table => print_table(headers, &items.iter().map(row).collect::<Vec<_>>()),
+    }
+}
+
+pub fn print_table(headers: &[&str], rows: &[Vec<String>]) {
+    let mut widths: Vec<usize> = headers
+        .iter()
+        .map(|header| header.chars().count())
+        .collect();
+    for row in rows {
+        for (width, cell) in widths.iter_mut().zip(row) {
+            *width = (*width).max(cell.chars().count());
+        }
+    }
+
+    let headers: Vec<String> = headers.iter().map(|header| header.to_string()).collect();
+    print_row(&widths, &headers);
+    for row in rows {
+        print_row(&widths, row);
+    }
+}
+
+fn print_row(widths: &[usize], cells: &[String]) {
+    let line: Vec<String> = widths
+        .iter()
+        .zip(cells)
+        .map(|(width, cell)| {
+            let padding = width - cell.chars().count();
+            format!("{}{}", cell, " ".repeat(padding))
+        })
+        .collect();
+    println!("{}", line.join("  ").trim_end());
+}
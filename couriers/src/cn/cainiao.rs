@@ -5,9 +5,8 @@ use nipper::Document;
 use regex::Regex;
 use serde_json::Value;
 
-use crate::{
-    structs::{Courier, TrackingError, TrackingResult},
-    tracker,
+use crate::structs::{
+    Courier, TrackingDetail, TrackingError, TrackingInfo, TrackingResult, TIME_FORMAT,
 };
 
 pub struct Cainiao {}
@@ -59,7 +58,7 @@ impl Courier for Cainiao {
             return Err(TrackingError::NotExistsTrackingNumber);
         }
 
-        let mut tracks: Vec<tracker::TrackingDetail> = vec![];
+        let mut tracks: Vec<TrackingDetail> = vec![];
 
         for element in json["data"][0]["section2"]["detailList"]
             .as_array()
@@ -68,18 +67,19 @@ impl Courier for Cainiao {
             let datetime =
                 Seoul.datetime_from_str(element["time"].as_str().unwrap(), "%Y-%m-%d %H:%M:%S")?;
 
-            tracks.push(tracker::TrackingDetail {
-                time: datetime.format("%Y-%m-%d %H:%M:%S").to_string(),
+            tracks.push(TrackingDetail {
+                time: datetime.format(TIME_FORMAT).to_string(),
                 message: Some(element["desc"].as_str().unwrap().to_string()),
                 status: None,
                 location: None,
                 live_tracking_url: None,
+                courier_contact: None,
             });
         }
 
         tracks.reverse();
 
-        Ok(tracker::TrackingInfo {
+        Ok(TrackingInfo {
             id: Self::id().to_string(),
             name: Self::name().to_string(),
             url: url.to_string(),
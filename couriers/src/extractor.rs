@@ -0,0 +1,65 @@
+use once_cell::sync::Lazy;
+use regex::Regex;
+
+static CANDIDATE_REGEX: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"[0-9A-Za-z]+(?:[- ][0-9A-Za-z]+)*").unwrap());
+
+pub fn candidates(text: &str) -> Vec<String> {
+    let mut candidates = Vec::new();
+
+    for found in CANDIDATE_REGEX.find_iter(text) {
+        let found = found.as_str();
+        let joined = found.replace(&['-', ' '][..], "");
+        let words: Vec<String> = found.split(' ').map(|word| word.replace('-', "")).collect();
+        // Numbers written in digit groups often follow a word, e.g. "Invoice 1234 5678 9012".
+        let digit_runs = words
+            .split(|word| !word.chars().all(|c| c.is_ascii_digit()))
+            .filter(|run| run.len() > 1)
+            .map(|run| run.concat());
+
+        for candidate in std::iter::once(joined)
+            .chain(digit_runs)
+            .chain(words.iter().cloned())
+        {
+            if candidate.len() >= 8
+                && candidate.chars().any(|c| c.is_ascii_digit())
+                && !candidates.contains(&candidate)
+            {
+                candidates.push(candidate);
+            }
+        }
+    }
+
+    candidates
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn joins_dashed_and_spaced_numbers() {
+        assert_eq!(candidates("6543-2109-8765"), vec!["654321098765"]);
+        assert_eq!(candidates("6543 2109 8765"), vec!["654321098765"]);
+    }
+
+    #[test]
+    fn joins_digit_groups_after_a_word() {
+        let candidates = candidates("Invoice 1234 5678 9012");
+        assert!(candidates.contains(&"123456789012".to_string()));
+    }
+
+    #[test]
+    fn keeps_single_words() {
+        let candidates = candidates("송장번호 123456789012 입니다");
+        assert_eq!(candidates, vec!["123456789012"]);
+
+        let candidates = candidates("Order ABC12345 shipped");
+        assert!(candidates.contains(&"ABC12345".to_string()));
+    }
+
+    #[test]
+    fn skips_short_and_digitless_words() {
+        assert!(candidates("1234, abcdefghij").is_empty());
+    }
+}
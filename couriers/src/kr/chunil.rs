@@ -3,10 +3,7 @@ use chrono::TimeZone;
 use chrono_tz::Asia::Seoul;
 use nipper::Document;
 
-use crate::{
-    structs::{Courier, TrackingError},
-    tracker,
-};
+use crate::structs::{Courier, TrackingDetail, TrackingError, TrackingInfo, TIME_FORMAT};
 
 pub struct Chunil {}
 
@@ -44,7 +41,7 @@ impl Courier for Chunil {
 
         let document = Document::from(&body);
 
-        let mut tracks: Vec<tracker::TrackingDetail> = vec![];
+        let mut tracks: Vec<TrackingDetail> = vec![];
 
         for element in document.select("#tracking > tbody > tr").iter() {
             if element.html().contains("날짜") {
@@ -56,16 +53,17 @@ impl Courier for Chunil {
                 "%Y-%m-%d %H:%M:%S",
             )?;
 
-            tracks.push(tracker::TrackingDetail {
-                time: datetime.format("%Y-%m-%d %H:%M:%S").to_string(),
+            tracks.push(TrackingDetail {
+                time: datetime.format(TIME_FORMAT).to_string(),
                 message: None,
                 status: Some(element.select("td:nth-child(4)").text().to_string()),
                 location: Some(element.select("td:nth-child(2)").text().to_string()),
                 live_tracking_url: None,
+                courier_contact: None,
             });
         }
 
-        Ok(tracker::TrackingInfo {
+        Ok(TrackingInfo {
             id: Self::id().to_string(),
             name: Self::name().to_string(),
             url: url.to_string(),
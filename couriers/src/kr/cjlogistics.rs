@@ -4,9 +4,8 @@ use chrono_tz::Asia::Seoul;
 use nipper::Document;
 use serde_json::Value;
 
-use crate::{
-    structs::{Courier, TrackingError, TrackingResult},
-    tracker,
+use crate::structs::{
+    Courier, TrackingDetail, TrackingError, TrackingInfo, TrackingResult, TIME_FORMAT,
 };
 
 pub struct Cjlogistics {}
@@ -77,7 +76,7 @@ impl Courier for Cjlogistics {
             return Err(TrackingError::NotExistsTrackingNumber);
         }
 
-        let mut tracks: Vec<tracker::TrackingDetail> = vec![];
+        let mut tracks: Vec<TrackingDetail> = vec![];
         let detail = &json["parcelResultMap"]["resultList"][0];
 
         for element in json["parcelDetailResultMap"]["resultList"]
@@ -97,8 +96,8 @@ impl Courier for Cjlogistics {
             let datetime = Seoul
                 .datetime_from_str(element["dTime"].as_str().unwrap(), "%Y-%m-%d %H:%M:%S.%f")?;
 
-            tracks.push(tracker::TrackingDetail {
-                time: datetime.format("%Y-%m-%d %H:%M:%S").to_string(),
+            tracks.push(TrackingDetail {
+                time: datetime.format(TIME_FORMAT).to_string(),
                 message: Some(
                     element["crgNm"]
                         .as_str()
@@ -109,10 +108,11 @@ impl Courier for Cjlogistics {
                 status: Some(element["scanNm"].as_str().unwrap().to_string()),
                 location: Some(element["regBranNm"].as_str().unwrap().to_string()),
                 live_tracking_url,
+                courier_contact: None,
             });
         }
 
-        Ok(tracker::TrackingInfo {
+        Ok(TrackingInfo {
             id: Self::id().to_string(),
             name: Self::name().to_string(),
             url: url.to_string(),
@@ -0,0 +1,77 @@
+use once_cell::sync::Lazy;
+use regex::Regex;
+
+use crate::structs::CourierContact;
+
+/// The name only counts after a `:` so words like "담당자에게" don't become one.
+static CONTACT_REGEX: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(
+        r"담당(?:자|기사|\s*SM)?\s*(?:[:：]\s*([가-힣A-Za-z*]{2,}))?[\s,(]*(0\d{1,2}-?\d{3,4}-?\d{4})?",
+    )
+    .unwrap()
+});
+
+/// Finds the delivery driver mentioned in a tracking event, like `배송담당: 홍길동 010-1234-5678`.
+pub(crate) fn parse_courier_contact(text: &str) -> Option<CourierContact> {
+    let captures = CONTACT_REGEX.captures(text)?;
+    let name = captures.get(1).map(|name| name.as_str().to_string());
+    let phone = captures.get(2).map(|phone| phone.as_str().to_string());
+    if name.is_none() && phone.is_none() {
+        None
+    } else {
+        Some(CourierContact { name, phone })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn contact(name: Option<&str>, phone: Option<&str>) -> Option<CourierContact> {
+        Some(CourierContact {
+            name: name.map(str::to_string),
+            phone: phone.map(str::to_string),
+        })
+    }
+
+    #[test]
+    fn parses_hanjin_delivery_status() {
+        assert_eq!(
+            parse_courier_contact(
+                "배송출발 (배달예정시간 : 14~16시) 고객님의 상품을 배송할 예정입니다. 배송담당: 홍*동 010-1234-5678"
+            ),
+            contact(Some("홍*동"), Some("010-1234-5678"))
+        );
+    }
+
+    #[test]
+    fn parses_lotte_delivery_message() {
+        assert_eq!(
+            parse_courier_contact(
+                "배송 출발 (배달 예정 시간 : 14~16시). (담당 SM : 김철수 010-9876-5432)"
+            ),
+            contact(Some("김철수"), Some("010-9876-5432"))
+        );
+        assert_eq!(
+            parse_courier_contact("배달 준비 (담당기사：이영희(01098765432))"),
+            contact(Some("이영희"), Some("01098765432"))
+        );
+    }
+
+    #[test]
+    fn parses_phone_without_name() {
+        assert_eq!(
+            parse_courier_contact("배송출발 (담당 02-123-4567)"),
+            contact(None, Some("02-123-4567"))
+        );
+    }
+
+    #[test]
+    fn ignores_words_after_the_title() {
+        assert_eq!(
+            parse_courier_contact("부재중 방문, 담당자에게 연락 바랍니다."),
+            None
+        );
+        assert_eq!(parse_courier_contact("간선상차"), None);
+    }
+}
@@ -3,10 +3,7 @@ use chrono::TimeZone;
 use chrono_tz::Asia::Seoul;
 use nipper::Document;
 
-use crate::{
-    structs::{Courier, TrackingError},
-    tracker,
-};
+use crate::structs::{Courier, TrackingDetail, TrackingError, TrackingInfo, TIME_FORMAT};
 
 use super::cjlogistics::Cjlogistics;
 
@@ -57,7 +54,7 @@ impl Courier for Cupost {
 
         let document = Document::from(&body);
 
-        let mut tracks: Vec<tracker::TrackingDetail> = vec![];
+        let mut tracks: Vec<TrackingDetail> = vec![];
 
         for element in document
             .select("#gotoMainContents > table:nth-child(10) > tbody > tr")
@@ -68,16 +65,17 @@ impl Courier for Cupost {
                 "%Y.%m.%d %H:%M",
             )?;
 
-            tracks.push(tracker::TrackingDetail {
-                time: datetime.format("%Y-%m-%d %H:%M:%S").to_string(),
+            tracks.push(TrackingDetail {
+                time: datetime.format(TIME_FORMAT).to_string(),
                 message: Some(element.select("td:nth-child(3)").text().trim().to_string()),
                 status: None,
                 location: Some(element.select("td:nth-child(2)").text().trim().to_string()),
                 live_tracking_url: None,
+                courier_contact: None,
             });
         }
 
-        Ok(tracker::TrackingInfo {
+        Ok(TrackingInfo {
             id: Self::id().to_string(),
             name: Self::name().to_string(),
             url: "https://www.cupost.co.kr/postbox/delivery/local.cupost".to_string(),
@@ -3,10 +3,7 @@ use chrono::TimeZone;
 use chrono_tz::Asia::Seoul;
 use nipper::Document;
 
-use crate::{
-    structs::{Courier, TrackingError},
-    tracker,
-};
+use crate::structs::{Courier, TrackingDetail, TrackingError, TrackingInfo, TIME_FORMAT};
 
 pub struct Daesin {}
 
@@ -45,7 +42,7 @@ impl Courier for Daesin {
 
         let document = Document::from(&body);
 
-        let mut tracks: Vec<tracker::TrackingDetail> = vec![];
+        let mut tracks: Vec<TrackingDetail> = vec![];
 
         for element in document
             .select("#printarea > table:nth-child(5) > tbody > tr")
@@ -60,8 +57,8 @@ impl Courier for Daesin {
             let datetime = Seoul
                 .datetime_from_str(&element.select("td:nth-child(4)").text(), "%Y-%m-%d %H:%M")?;
 
-            tracks.push(tracker::TrackingDetail {
-                time: datetime.format("%Y-%m-%d %H:%M:%S").to_string(),
+            tracks.push(TrackingDetail {
+                time: datetime.format(TIME_FORMAT).to_string(),
                 message: None,
                 status: Some(if dealer_type == "발송취급점" {
                     "인수".to_string()
@@ -74,6 +71,7 @@ impl Courier for Daesin {
                     element.select("td:nth-child(2)").text().trim()
                 )),
                 live_tracking_url: None,
+                courier_contact: None,
             });
 
             let start_time = element.select("td:nth-child(5)").text().to_string();
@@ -81,8 +79,8 @@ impl Courier for Daesin {
             if !start_time.is_empty() {
                 let datetime = Seoul.datetime_from_str(&start_time, "%Y-%m-%d %H:%M")?;
 
-                tracks.push(tracker::TrackingDetail {
-                    time: datetime.format("%Y-%m-%d %H:%M:%S").to_string(),
+                tracks.push(TrackingDetail {
+                    time: datetime.format(TIME_FORMAT).to_string(),
                     message: None,
                     status: Some(if dealer_type == "도착취급점" {
                         "배송완료".to_string()
@@ -95,11 +93,12 @@ impl Courier for Daesin {
                         element.select("td:nth-child(2)").text().trim()
                     )),
                     live_tracking_url: None,
+                    courier_contact: None,
                 });
             }
         }
 
-        Ok(tracker::TrackingInfo {
+        Ok(TrackingInfo {
             id: Self::id().to_string(),
             name: Self::name().to_string(),
             url: url.to_string(),
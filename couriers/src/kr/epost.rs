@@ -4,10 +4,7 @@ use chrono_tz::Asia::Seoul;
 use nipper::Document;
 use regex::Regex;
 
-use crate::{
-    structs::{Courier, TrackingError},
-    tracker,
-};
+use crate::structs::{Courier, TrackingDetail, TrackingError, TrackingInfo, TIME_FORMAT};
 
 pub struct Epost {}
 
@@ -45,7 +42,7 @@ impl Courier for Epost {
 
         let document = Document::from(&body);
 
-        let mut tracks: Vec<tracker::TrackingDetail> = vec![];
+        let mut tracks: Vec<TrackingDetail> = vec![];
         let space_regex = Regex::new(r"\s+")?;
 
         for element in document.select("#processTable > tbody > tr").iter() {
@@ -75,12 +72,13 @@ impl Courier for Epost {
                 "%Y.%m.%d %H:%M",
             )?;
 
-            tracks.push(tracker::TrackingDetail {
-                time: datetime.format("%Y-%m-%d %H:%M:%S").to_string(),
+            tracks.push(TrackingDetail {
+                time: datetime.format(TIME_FORMAT).to_string(),
                 message: None,
                 status: Some(status.to_string()),
                 location: Some(location.to_string()),
                 live_tracking_url: None,
+                courier_contact: None,
             });
         }
 
@@ -95,7 +93,7 @@ impl Courier for Epost {
             .get(1)
             .map_or("", |c| c.as_str());
 
-        Ok(tracker::TrackingInfo {
+        Ok(TrackingInfo {
             id: Self::id().to_string(),
             name: Self::name().to_string(),
             url: url.to_string(),
@@ -4,10 +4,7 @@ use chrono_tz::Asia::Seoul;
 use nipper::Document;
 use regex::Regex;
 
-use crate::{
-    structs::{Courier, TrackingError},
-    tracker,
-};
+use crate::structs::{Courier, TrackingDetail, TrackingError, TrackingInfo, TIME_FORMAT};
 
 pub struct EpostEMS {}
 
@@ -46,7 +43,7 @@ impl Courier for EpostEMS {
 
         let document = Document::from(&body);
 
-        let mut tracks: Vec<tracker::TrackingDetail> = vec![];
+        let mut tracks: Vec<TrackingDetail> = vec![];
 
         for element in document
             .select("#print > table.table_col.detail_off.ma_t_5 > tbody > tr")
@@ -55,16 +52,17 @@ impl Courier for EpostEMS {
             let datetime = Seoul
                 .datetime_from_str(&element.select("td:nth-child(1)").text(), "%Y.%m.%d %H:%M")?;
 
-            tracks.push(tracker::TrackingDetail {
-                time: datetime.format("%Y-%m-%d %H:%M:%S").to_string(),
+            tracks.push(TrackingDetail {
+                time: datetime.format(TIME_FORMAT).to_string(),
                 message: None,
                 status: Some(element.select("td:nth-child(2)").text().trim().to_string()),
                 location: Some(element.select("td:nth-child(3)").text().trim().to_string()),
                 live_tracking_url: None,
+                courier_contact: None,
             });
         }
 
-        Ok(tracker::TrackingInfo {
+        Ok(TrackingInfo {
             id: Self::id().to_string(),
             name: Self::name().to_string(),
             url: url.to_string(),
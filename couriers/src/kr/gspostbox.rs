@@ -4,10 +4,7 @@ use chrono_tz::Asia::Seoul;
 use regex::Regex;
 use serde_json::Value;
 
-use crate::{
-    structs::{Courier, TrackingError},
-    tracker,
-};
+use crate::structs::{Courier, TrackingDetail, TrackingError, TrackingInfo, TIME_FORMAT};
 
 pub struct Gspostbox {}
 
@@ -56,22 +53,23 @@ impl Courier for Gspostbox {
 
         let json: Value = serde_json::from_str(json)?;
 
-        let mut tracks: Vec<tracker::TrackingDetail> = vec![];
+        let mut tracks: Vec<TrackingDetail> = vec![];
 
         for element in json["trackingDetails"].as_array().unwrap() {
             let datetime = Seoul
                 .datetime_from_str(&element["transTime"].as_str().unwrap(), "%Y-%m-%dT%H:%M:%S")?;
 
-            tracks.push(tracker::TrackingDetail {
-                time: datetime.format("%Y-%m-%d %H:%M:%S").to_string(),
+            tracks.push(TrackingDetail {
+                time: datetime.format(TIME_FORMAT).to_string(),
                 message: None,
                 status: Some(element["transKind"].as_str().unwrap().replace("  ", " ")),
                 location: Some(element["transWhere"].as_str().unwrap().to_string()),
                 live_tracking_url: None,
+                courier_contact: None,
             });
         }
 
-        Ok(tracker::TrackingInfo {
+        Ok(TrackingInfo {
             id: Self::id().to_string(),
             name: format!(
                 "{} {}",
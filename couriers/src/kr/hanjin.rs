@@ -3,10 +3,8 @@ use chrono::TimeZone;
 use chrono_tz::Asia::Seoul;
 use nipper::Document;
 
-use crate::{
-    structs::{Courier, TrackingError},
-    tracker,
-};
+use super::contact::parse_courier_contact;
+use crate::structs::{Courier, TrackingDetail, TrackingError, TrackingInfo, TIME_FORMAT};
 
 pub struct Hanjin {}
 
@@ -51,7 +49,7 @@ impl Courier for Hanjin {
 
         let document = Document::from(&body);
 
-        let mut tracks: Vec<tracker::TrackingDetail> = vec![];
+        let mut tracks: Vec<TrackingDetail> = vec![];
 
         for element in document
             .select("#delivery-wr > div > div.waybill-tbl > table > tbody > tr")
@@ -66,16 +64,20 @@ impl Courier for Hanjin {
                 "%Y-%m-%d %H:%M",
             )?;
 
-            tracks.push(tracker::TrackingDetail {
-                time: datetime.format("%Y-%m-%d %H:%M:%S").to_string(),
+            let status = element.select(".stateDesc").text().replace("  ", " ");
+            let courier_contact = parse_courier_contact(&status);
+
+            tracks.push(TrackingDetail {
+                time: datetime.format(TIME_FORMAT).to_string(),
                 message: None,
-                status: Some(element.select(".stateDesc").text().replace("  ", " ")),
+                status: Some(status),
                 location: Some(element.select("td.w-org").text().to_string()),
                 live_tracking_url: None,
+                courier_contact,
             });
         }
 
-        Ok(tracker::TrackingInfo {
+        Ok(TrackingInfo {
             id: Self::id().to_string(),
             name: Self::name().to_string(),
             url: url.to_string(),
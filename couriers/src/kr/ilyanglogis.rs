@@ -4,10 +4,7 @@ use chrono_tz::Asia::Seoul;
 use encoding::{all::WINDOWS_949, Encoding};
 use nipper::Document;
 
-use crate::{
-    structs::{Courier, TrackingError},
-    tracker,
-};
+use crate::structs::{Courier, TrackingDetail, TrackingError, TrackingInfo, TIME_FORMAT};
 
 pub struct IlyangLogis {}
 
@@ -48,7 +45,7 @@ impl Courier for IlyangLogis {
 
         let document = Document::from(&body);
 
-        let mut tracks: Vec<tracker::TrackingDetail> = vec![];
+        let mut tracks: Vec<TrackingDetail> = vec![];
 
         for element in document
             .select("#popContainer > div > table > tbody > tr")
@@ -63,16 +60,17 @@ impl Courier for IlyangLogis {
                 "%Y-%m-%d %H:%M",
             )?;
 
-            tracks.push(tracker::TrackingDetail {
-                time: datetime.format("%Y-%m-%d %H:%M:%S").to_string(),
+            tracks.push(TrackingDetail {
+                time: datetime.format(TIME_FORMAT).to_string(),
                 message: None,
                 status: Some(element.select("td:nth-child(3)").text().to_string()),
                 location: Some(element.select("td:nth-child(4)").text().to_string()),
                 live_tracking_url: None,
+                courier_contact: None,
             });
         }
 
-        Ok(tracker::TrackingInfo {
+        Ok(TrackingInfo {
             id: Self::id().to_string(),
             name: Self::name().to_string(),
             url: url.to_string(),
@@ -3,9 +3,8 @@ use chrono::TimeZone;
 use chrono_tz::Asia::Seoul;
 use serde_json::Value;
 
-use crate::{
-    structs::{Courier, TrackingError, TrackingResult},
-    tracker,
+use crate::structs::{
+    Courier, TrackingDetail, TrackingError, TrackingInfo, TrackingResult, TIME_FORMAT,
 };
 
 pub struct Kyoungdong {}
@@ -43,7 +42,7 @@ impl Courier for Kyoungdong {
             return Err(TrackingError::NotExistsTrackingNumber);
         }
 
-        let mut tracks: Vec<tracker::TrackingDetail> = vec![];
+        let mut tracks: Vec<TrackingDetail> = vec![];
 
         for element in json["items"].as_array().unwrap() {
             let datetime = Seoul.datetime_from_str(
@@ -51,16 +50,17 @@ impl Courier for Kyoungdong {
                 "%Y-%m-%d %H:%M:%S.%f",
             )?;
 
-            tracks.push(tracker::TrackingDetail {
-                time: datetime.format("%Y-%m-%d %H:%M:%S").to_string(),
+            tracks.push(TrackingDetail {
+                time: datetime.format(TIME_FORMAT).to_string(),
                 message: None,
                 status: Some(element["stat"].as_str().unwrap().to_string()),
                 location: Some(element["location"].as_str().unwrap().to_string()),
                 live_tracking_url: None,
+                courier_contact: None,
             });
         }
 
-        Ok(tracker::TrackingInfo {
+        Ok(TrackingInfo {
             id: Self::id().to_string(),
             name: Self::name().to_string(),
             url: "https://kdexp.com/main.kd".to_string(),
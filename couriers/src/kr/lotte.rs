@@ -3,10 +3,8 @@ use chrono::TimeZone;
 use chrono_tz::Asia::Seoul;
 use nipper::Document;
 
-use crate::{
-    structs::{Courier, TrackingError},
-    tracker,
-};
+use super::contact::parse_courier_contact;
+use crate::structs::{Courier, TrackingDetail, TrackingError, TrackingInfo, TIME_FORMAT};
 
 pub struct Lotte {}
 
@@ -49,7 +47,7 @@ impl Courier for Lotte {
 
         let document = Document::from(&body);
 
-        let mut tracks: Vec<tracker::TrackingDetail> = vec![];
+        let mut tracks: Vec<TrackingDetail> = vec![];
 
         for element in document
             .select("#contents > div > div.contArea > table:nth-child(4) > tbody > tr")
@@ -62,24 +60,26 @@ impl Courier for Lotte {
             let datetime = Seoul
                 .datetime_from_str(&element.select("td:nth-child(2)").text(), "%Y-%m-%d %H:%M")?;
 
-            tracks.push(tracker::TrackingDetail {
-                time: datetime.format("%Y-%m-%d %H:%M:%S").to_string(),
-                message: Some(
-                    element
-                        .select("td:nth-child(4)")
-                        .text()
-                        .replace("  ", " ")
-                        .replace(".(", ". ("),
-                ),
+            let message = element
+                .select("td:nth-child(4)")
+                .text()
+                .replace("  ", " ")
+                .replace(".(", ". (");
+            let courier_contact = parse_courier_contact(&message);
+
+            tracks.push(TrackingDetail {
+                time: datetime.format(TIME_FORMAT).to_string(),
+                message: Some(message),
                 status: Some(element.select("td:nth-child(1)").text().to_string()),
                 location: Some(element.select("td:nth-child(3)").text().trim().to_string()),
                 live_tracking_url: None,
+                courier_contact,
             });
         }
 
         tracks.reverse();
 
-        Ok(tracker::TrackingInfo {
+        Ok(TrackingInfo {
             id: Self::id().to_string(),
             name: Self::name().to_string(),
             url: "https://www.lotteglogis.com/home/main".to_string(),
@@ -1,5 +1,6 @@
 mod chunil;
 mod cjlogistics;
+mod contact;
 mod cupost;
 mod daesin;
 mod epost;
@@ -0,0 +1,157 @@
+use serde::Serialize;
+
+pub use cn::*;
+pub use kr::*;
+pub use structs::{
+    Courier, CourierContact, TrackingDetail, TrackingError, TrackingInfo, TrackingResult,
+    TIME_FORMAT,
+};
+pub use us::*;
+
+mod cn;
+mod extractor;
+mod kr;
+mod structs;
+mod us;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+pub struct CourierInfo {
+    pub id: &'static str,
+    pub name: &'static str,
+}
+
+impl CourierInfo {
+    pub fn of<C: Courier>() -> Self {
+        Self {
+            id: C::id(),
+            name: C::name(),
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub struct DetectedTrackingNumber {
+    pub tracking_number: String,
+    pub couriers: Vec<CourierInfo>,
+}
+
+/// Every courier that [`track`] accepts.
+pub fn couriers() -> Vec<CourierInfo> {
+    vec![
+        CourierInfo::of::<Chunil>(),
+        CourierInfo::of::<Cjlogistics>(),
+        CourierInfo::of::<Cupost>(),
+        CourierInfo::of::<Daesin>(),
+        CourierInfo::of::<Epost>(),
+        CourierInfo::of::<EpostEMS>(),
+        CourierInfo::of::<Gspostbox>(),
+        CourierInfo::of::<Hanjin>(),
+        CourierInfo::of::<IlyangLogis>(),
+        CourierInfo::of::<Kyoungdong>(),
+        CourierInfo::of::<Logen>(),
+        CourierInfo::of::<Lotte>(),
+        CourierInfo::of::<Fedex>(),
+        CourierInfo::of::<Warpex>(),
+    ]
+}
+
+pub fn is_supported(courier_id: &str) -> bool {
+    couriers().iter().any(|courier| courier.id == courier_id)
+}
+
+/// Tracks `tracking_number` with the courier, or `None` if the courier id is unknown.
+pub async fn track(courier_id: &str, tracking_number: &str) -> Option<TrackingResult> {
+    let result = match courier_id {
+        // "cn.cainiao" => Cainiao::track(tracking_number).await,
+        "kr.chunil" => Chunil::track(tracking_number).await,
+        "kr.cjlogistics" => Cjlogistics::track(tracking_number).await,
+        "kr.cupost" => Cupost::track(tracking_number).await,
+        "kr.daesin" => Daesin::track(tracking_number).await,
+        "kr.epost" => Epost::track(tracking_number).await,
+        "kr.epostems" => EpostEMS::track(tracking_number).await,
+        "kr.gspostbox" => Gspostbox::track(tracking_number).await,
+        "kr.hanjin" => Hanjin::track(tracking_number).await,
+        "kr.ilyanglogis" => IlyangLogis::track(tracking_number).await,
+        "kr.kyoungdong" => Kyoungdong::track(tracking_number).await,
+        "kr.logen" => Logen::track(tracking_number).await,
+        "kr.lotte" => Lotte::track(tracking_number).await,
+        "us.fedex" => Fedex::track(tracking_number).await,
+        "us.warpex" => Warpex::track(tracking_number).await,
+        _ => return None,
+    };
+    Some(result)
+}
+
+/// Couriers whose tracking number format matches `tracking_number`.
+pub fn detect(tracking_number: &str) -> Vec<CourierInfo> {
+    let mut couriers = Vec::new();
+
+    // if Cainiao::validate(tracking_number) {
+    //     couriers.push(CourierInfo::of::<Cainiao>());
+    // }
+
+    if Cjlogistics::validate(tracking_number) {
+        couriers.push(CourierInfo::of::<Cjlogistics>());
+    }
+    if Epost::validate(tracking_number) {
+        couriers.push(CourierInfo::of::<Epost>());
+    }
+    if EpostEMS::validate(tracking_number) {
+        couriers.push(CourierInfo::of::<EpostEMS>());
+    }
+    if Hanjin::validate(tracking_number) {
+        couriers.push(CourierInfo::of::<Hanjin>());
+    }
+    if Logen::validate(tracking_number) {
+        couriers.push(CourierInfo::of::<Logen>());
+    }
+    if Lotte::validate(tracking_number) {
+        couriers.push(CourierInfo::of::<Lotte>());
+    }
+    if Cupost::validate(tracking_number) {
+        couriers.push(CourierInfo::of::<Cupost>());
+    }
+    if Gspostbox::validate(tracking_number) {
+        couriers.push(CourierInfo::of::<Gspostbox>());
+    }
+
+    if Chunil::validate(tracking_number) {
+        couriers.push(CourierInfo::of::<Chunil>());
+    }
+    if Daesin::validate(tracking_number) {
+        couriers.push(CourierInfo::of::<Daesin>());
+    }
+    if IlyangLogis::validate(tracking_number) {
+        couriers.push(CourierInfo::of::<IlyangLogis>());
+    }
+    if Kyoungdong::validate(tracking_number) {
+        couriers.push(CourierInfo::of::<Kyoungdong>());
+    }
+
+    if Fedex::validate(tracking_number) {
+        couriers.push(CourierInfo::of::<Fedex>());
+    }
+    if Warpex::validate(tracking_number) {
+        couriers.push(CourierInfo::of::<Warpex>());
+    }
+
+    couriers
+}
+
+/// Finds tracking numbers in free text, such as a pasted shipping notice.
+pub fn extract(text: &str) -> Vec<DetectedTrackingNumber> {
+    extractor::candidates(text)
+        .into_iter()
+        .filter_map(|tracking_number| {
+            let couriers = detect(&tracking_number);
+            if couriers.is_empty() {
+                None
+            } else {
+                Some(DetectedTrackingNumber {
+                    tracking_number,
+                    couriers,
+                })
+            }
+        })
+        .collect()
+}
@@ -0,0 +1,111 @@
+use std::fmt;
+
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+
+#[async_trait]
+pub trait Courier {
+    fn id() -> &'static str;
+    fn name() -> &'static str;
+    fn validate(tracking_number: &str) -> bool;
+    async fn track(tracking_number: &str) -> TrackingResult;
+}
+
+pub type TrackingResult = Result<TrackingInfo, TrackingError>;
+
+/// Format of [`TrackingDetail::time`], RFC 3339 in the offset the courier reported the event in.
+pub const TIME_FORMAT: &str = "%Y-%m-%dT%H:%M:%S%:z";
+
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct TrackingInfo {
+    pub id: String,
+    pub name: String,
+    pub url: String,
+    pub tracking_number: String,
+    pub is_delivered: bool,
+    pub sender: Option<String>,
+    pub receiver: Option<String>,
+    pub product: Option<String>,
+    pub tracks: Vec<TrackingDetail>,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct TrackingDetail {
+    /// When the event happened, formatted with [`TIME_FORMAT`].
+    pub time: String,
+    pub message: Option<String>,
+    pub status: Option<String>,
+    pub location: Option<String>,
+    pub live_tracking_url: Option<String>,
+    pub courier_contact: Option<CourierContact>,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct CourierContact {
+    pub name: Option<String>,
+    pub phone: Option<String>,
+}
+
+#[derive(Debug)]
+pub enum TrackingError {
+    RequestFailed(String),
+    WrongTrackingNumber(String),
+    NotExistsTrackingNumber,
+    ParsingError(String),
+}
+
+impl TrackingError {
+    pub fn kind(&self) -> &'static str {
+        match self {
+            TrackingError::RequestFailed(_) => "request_failed",
+            TrackingError::WrongTrackingNumber(_) => "wrong_tracking_number",
+            TrackingError::NotExistsTrackingNumber => "not_exists_tracking_number",
+            TrackingError::ParsingError(_) => "parsing_error",
+        }
+    }
+}
+
+impl fmt::Display for TrackingError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TrackingError::RequestFailed(err) => write!(f, "request failed: {}", err),
+            TrackingError::WrongTrackingNumber(expected) => {
+                write!(f, "wrong tracking number, expected {}", expected)
+            }
+            TrackingError::NotExistsTrackingNumber => f.write_str("tracking number does not exist"),
+            TrackingError::ParsingError(err) => write!(f, "parsing failed: {}", err),
+        }
+    }
+}
+
+impl std::error::Error for TrackingError {}
+
+impl From<reqwest::Error> for TrackingError {
+    fn from(error: reqwest::Error) -> Self {
+        TrackingError::RequestFailed(error.to_string())
+    }
+}
+
+impl From<reqwest::header::ToStrError> for TrackingError {
+    fn from(error: reqwest::header::ToStrError) -> Self {
+        TrackingError::RequestFailed(error.to_string())
+    }
+}
+
+impl From<regex::Error> for TrackingError {
+    fn from(error: regex::Error) -> Self {
+        TrackingError::ParsingError(error.to_string())
+    }
+}
+
+impl From<chrono::ParseError> for TrackingError {
+    fn from(error: chrono::ParseError) -> Self {
+        TrackingError::ParsingError(error.to_string())
+    }
+}
+
+impl From<serde_json::Error> for TrackingError {
+    fn from(error: serde_json::Error) -> Self {
+        TrackingError::ParsingError(error.to_string())
+    }
+}
@@ -1,12 +1,8 @@
 use async_trait::async_trait;
-use chrono::{DateTime, TimeZone};
-use chrono_tz::Asia::Seoul;
+use chrono::DateTime;
 use serde_json::{json, Value};
 
-use crate::{
-    structs::{Courier, TrackingError},
-    tracker,
-};
+use crate::structs::{Courier, TrackingDetail, TrackingError, TrackingInfo, TIME_FORMAT};
 
 pub struct Fedex {}
 
@@ -75,7 +71,7 @@ impl Courier for Fedex {
             return Err(TrackingError::NotExistsTrackingNumber);
         }
 
-        let mut tracks: Vec<tracker::TrackingDetail> = vec![];
+        let mut tracks: Vec<TrackingDetail> = vec![];
 
         let package_info = &json["packageList"][0];
 
@@ -90,20 +86,19 @@ impl Courier for Fedex {
                 "%Y-%m-%d %H:%M:%S %:z",
             )?;
 
-            let datetime = Seoul.from_utc_datetime(&datetime.naive_utc());
-
-            tracks.push(tracker::TrackingDetail {
-                time: datetime.format("%Y-%m-%d %H:%M:%S").to_string(),
+            tracks.push(TrackingDetail {
+                time: datetime.format(TIME_FORMAT).to_string(),
                 message: scan["status"].as_str().and_then(|s| Some(s.to_string())),
                 status: None,
                 location: Some(scan["scanLocation"].as_str().unwrap().to_string()),
                 live_tracking_url: None,
+                courier_contact: None,
             });
         }
 
         tracks.reverse();
 
-        Ok(tracker::TrackingInfo {
+        Ok(TrackingInfo {
             id: Self::id().to_string(),
             name: Self::name().to_string(),
             url: format!("https://www.fedex.com/fedextrack/?action=track&trackingnumber={}&cntry_code=kr&locale=ko_kr", tracking_number),
@@ -4,10 +4,7 @@ use chrono_tz::Asia::Seoul;
 use nipper::Document;
 use regex::Regex;
 
-use crate::{
-    structs::{Courier, TrackingError},
-    tracker,
-};
+use crate::structs::{Courier, TrackingDetail, TrackingError, TrackingInfo, TIME_FORMAT};
 
 pub struct Warpex {}
 
@@ -50,15 +47,15 @@ impl Courier for Warpex {
 
         let document = Document::from(&body);
 
-        let mut tracks: Vec<tracker::TrackingDetail> = vec![];
+        let mut tracks: Vec<TrackingDetail> = vec![];
         let message_regex = Regex::new(r"\s+").unwrap();
 
         for element in document.select("#history > ul > li").iter() {
             let datetime =
                 Seoul.datetime_from_str(&element.select(".date").text(), "%Y-%m-%d %p %I:%M:%S")?;
 
-            tracks.push(tracker::TrackingDetail {
-                time: datetime.format("%Y-%m-%d %H:%M:%S").to_string(),
+            tracks.push(TrackingDetail {
+                time: datetime.format(TIME_FORMAT).to_string(),
                 message: Some(
                     message_regex
                         .replace_all(&element.select(".txt").text().to_string(), " ")
@@ -68,6 +65,7 @@ impl Courier for Warpex {
                 status: None,
                 location: None,
                 live_tracking_url: None,
+                courier_contact: None,
             });
         }
 
@@ -83,7 +81,7 @@ impl Courier for Warpex {
             .html();
         let (_, receiver) = receiver.split_once("<br>").unwrap();
 
-        Ok(tracker::TrackingInfo {
+        Ok(TrackingInfo {
             id: Self::id().to_string(),
             name: Self::name().to_string(),
             url: url.to_string(),
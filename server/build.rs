@@ -1,6 +1,10 @@
+use std::{env, path::PathBuf};
+
 fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let out_dir = PathBuf::from(env::var("OUT_DIR")?);
     tonic_build::configure()
         .build_client(false)
+        .file_descriptor_set_path(out_dir.join("tracker_descriptor.bin"))
         .compile(&["../proto/tracker.proto"], &["../proto/"])?;
     Ok(())
 }
@@ -0,0 +1,233 @@
+use std::{
+    fs, io,
+    path::{Path, PathBuf},
+    sync::{Arc, RwLock},
+    time::SystemTime,
+};
+
+use serde::Deserialize;
+use tonic::{service::Interceptor, Request, Status};
+use tracing::{info, warn};
+
+const AUTHORIZATION_HEADER: &str = "authorization";
+const WILDCARD_NAMESPACE: &str = "*";
+
+#[derive(Clone, Debug, Deserialize)]
+pub struct Client {
+    pub name: String,
+    key: String,
+    pub namespaces: Vec<String>,
+    /// Allows the server-wide RPCs, like subscription stats and courier health.
+    #[serde(default)]
+    pub admin: bool,
+    /// Trusted to vouch for `is_chat_admin`, for front-ends that check chat permissions themselves.
+    #[serde(default)]
+    pub trust_chat_admin: bool,
+}
+
+impl Client {
+    pub fn is_admin(&self) -> bool {
+        self.admin
+            || self
+                .namespaces
+                .iter()
+                .any(|namespace| namespace == WILDCARD_NAMESPACE)
+    }
+
+    pub fn can_access(&self, user_id: &str) -> bool {
+        self.namespaces.iter().any(|namespace| {
+            namespace == WILDCARD_NAMESPACE
+                || user_id
+                    .strip_prefix(namespace.as_str())
+                    .map(|rest| rest.starts_with(':'))
+                    .unwrap_or(false)
+        })
+    }
+}
+
+pub struct ApiKeys {
+    path: PathBuf,
+    loaded: RwLock<(Option<SystemTime>, Vec<Client>)>,
+}
+
+impl ApiKeys {
+    pub fn open<P: AsRef<Path>>(path: P) -> io::Result<Self> {
+        let keys = Self {
+            path: path.as_ref().to_path_buf(),
+            loaded: RwLock::new((None, Vec::new())),
+        };
+        keys.reload()?;
+        Ok(keys)
+    }
+
+    fn reload(&self) -> io::Result<()> {
+        let modified = fs::metadata(&self.path)?.modified().ok();
+        if let Ok(loaded) = self.loaded.read() {
+            if loaded.0.is_some() && loaded.0 == modified {
+                return Ok(());
+            }
+        }
+
+        let clients: Vec<Client> = serde_json::from_str(&fs::read_to_string(&self.path)?)
+            .map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))?;
+        info!(clients = clients.len(), "Loaded API keys");
+        if let Ok(mut loaded) = self.loaded.write() {
+            *loaded = (modified, clients);
+        }
+        Ok(())
+    }
+
+    pub fn find(&self, key: &str) -> Option<Client> {
+        if let Err(err) = self.reload() {
+            warn!(error = %err, "Failed to reload API keys, keeping the previous ones");
+        }
+
+        let loaded = self.loaded.read().ok()?;
+        loaded
+            .1
+            .iter()
+            .find(|client| constant_time_eq(&client.key, key))
+            .cloned()
+    }
+}
+
+fn constant_time_eq(a: &str, b: &str) -> bool {
+    a.len() == b.len()
+        && a.bytes()
+            .zip(b.bytes())
+            .fold(0, |acc, (a, b)| acc | (a ^ b))
+            == 0
+}
+
+#[derive(Clone)]
+pub struct ApiKeyInterceptor {
+    keys: Option<Arc<ApiKeys>>,
+}
+
+impl ApiKeyInterceptor {
+    pub fn new(keys: Option<ApiKeys>) -> Self {
+        Self {
+            keys: keys.map(Arc::new),
+        }
+    }
+}
+
+impl Interceptor for ApiKeyInterceptor {
+    fn call(&mut self, mut request: Request<()>) -> Result<Request<()>, Status> {
+        let keys = match &self.keys {
+            Some(keys) => keys,
+            None => return Ok(request),
+        };
+
+        let key = request
+            .metadata()
+            .get(AUTHORIZATION_HEADER)
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| value.strip_prefix("Bearer "))
+            .ok_or_else(|| Status::unauthenticated("Missing API key"))?;
+        let client = keys
+            .find(key)
+            .ok_or_else(|| Status::unauthenticated("Invalid API key"))?;
+
+        request.extensions_mut().insert(client);
+        Ok(request)
+    }
+}
+
+pub fn authorize<T>(request: &Request<T>, user_id: &str) -> Result<(), Status> {
+    match request.extensions().get::<Client>() {
+        Some(client) if !client.can_access(user_id) => Err(Status::permission_denied(format!(
+            "Client {} can't access {}",
+            client.name, user_id
+        ))),
+        _ => Ok(()),
+    }
+}
+
+pub fn authorize_admin<T>(request: &Request<T>) -> Result<(), Status> {
+    match request.extensions().get::<Client>() {
+        Some(client) if !client.is_admin() => Err(Status::permission_denied(format!(
+            "Client {} is not an admin",
+            client.name
+        ))),
+        _ => Ok(()),
+    }
+}
+
+/// Whether the caller may claim `is_chat_admin`, every caller may while API keys are disabled.
+pub fn trusts_chat_admin<T>(request: &Request<T>) -> bool {
+    request
+        .extensions()
+        .get::<Client>()
+        .map(|client| client.trust_chat_admin)
+        .unwrap_or(true)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn client(json: &str) -> Client {
+        serde_json::from_str(json).unwrap()
+    }
+
+    fn request(client: Option<Client>) -> Request<()> {
+        let mut request = Request::new(());
+        if let Some(client) = client {
+            request.extensions_mut().insert(client);
+        }
+        request
+    }
+
+    #[test]
+    fn namespaces_limit_user_ids() {
+        let client = client(r#"{"name": "bot", "key": "k", "namespaces": ["telegram"]}"#);
+        assert!(client.can_access("telegram:42"));
+        assert!(!client.can_access("telegramx:42"));
+        assert!(!client.can_access("discord:42"));
+        assert!(!client.can_access("telegram"));
+
+        let request = request(Some(client));
+        assert!(authorize(&request, "telegram:42").is_ok());
+        assert!(authorize(&request, "slack:T1:C1").is_err());
+    }
+
+    #[test]
+    fn wildcard_accesses_everything() {
+        let client = client(r#"{"name": "ops", "key": "k", "namespaces": ["*"]}"#);
+        assert!(client.can_access("discord:42"));
+        assert!(client.is_admin());
+    }
+
+    #[test]
+    fn admin_rpcs_need_admin_scope() {
+        let user = client(r#"{"name": "cli", "key": "k", "namespaces": ["cli"]}"#);
+        assert!(authorize_admin(&request(Some(user))).is_err());
+
+        let admin =
+            client(r#"{"name": "bot", "key": "k", "namespaces": ["telegram"], "admin": true}"#);
+        assert!(authorize_admin(&request(Some(admin))).is_ok());
+
+        // Without API keys there is no client and nothing to check.
+        assert!(authorize_admin(&request(None)).is_ok());
+    }
+
+    #[test]
+    fn chat_admin_claims_need_trusted_keys() {
+        let cli = client(r#"{"name": "cli", "key": "k", "namespaces": ["cli"]}"#);
+        assert!(!trusts_chat_admin(&request(Some(cli))));
+
+        let bot = client(
+            r#"{"name": "bot", "key": "k", "namespaces": ["telegram"], "trust_chat_admin": true}"#,
+        );
+        assert!(trusts_chat_admin(&request(Some(bot))));
+        assert!(trusts_chat_admin(&request(None)));
+    }
+
+    #[test]
+    fn compares_keys_in_constant_time() {
+        assert!(constant_time_eq("secret", "secret"));
+        assert!(!constant_time_eq("secret", "secreT"));
+        assert!(!constant_time_eq("secret", "secrets"));
+    }
+}
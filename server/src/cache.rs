@@ -0,0 +1,87 @@
+use std::{
+    collections::HashMap,
+    sync::{PoisonError, RwLock},
+    time::{Duration, Instant},
+};
+
+use couriers::TrackingInfo;
+
+#[derive(Default)]
+pub struct SearchCache {
+    ttl: Option<Duration>,
+    entries: RwLock<HashMap<(String, String), (Instant, TrackingInfo)>>,
+}
+
+impl SearchCache {
+    pub fn new(ttl: Option<Duration>) -> Self {
+        Self {
+            ttl,
+            entries: RwLock::new(HashMap::new()),
+        }
+    }
+
+    pub fn get(&self, courier_id: &str, tracking_number: &str) -> Option<TrackingInfo> {
+        let ttl = self.ttl?;
+        let entries = self.entries.read().unwrap_or_else(PoisonError::into_inner);
+        entries
+            .get(&(courier_id.to_string(), tracking_number.to_string()))
+            .filter(|(cached_at, _)| cached_at.elapsed() < ttl)
+            .map(|(_, info)| info.clone())
+    }
+
+    pub fn insert(&self, courier_id: &str, tracking_number: &str, info: TrackingInfo) {
+        let ttl = match self.ttl {
+            Some(ttl) => ttl,
+            None => return,
+        };
+        // Entries are replaced whole, so a panic while holding the lock can't leave one torn.
+        let mut entries = self.entries.write().unwrap_or_else(PoisonError::into_inner);
+        entries.retain(|_, (cached_at, _)| cached_at.elapsed() < ttl);
+        entries.insert(
+            (courier_id.to_string(), tracking_number.to_string()),
+            (Instant::now(), info),
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn info(tracking_number: &str) -> TrackingInfo {
+        TrackingInfo {
+            tracking_number: tracking_number.to_string(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn caches_until_the_ttl() {
+        let cache = SearchCache::new(Some(Duration::from_secs(60)));
+        cache.insert("kr.cjlogistics", "1234", info("1234"));
+        assert!(cache.get("kr.cjlogistics", "1234").is_some());
+        assert!(cache.get("kr.hanjin", "1234").is_none());
+
+        let cache = SearchCache::new(Some(Duration::ZERO));
+        cache.insert("kr.cjlogistics", "1234", info("1234"));
+        assert!(cache.get("kr.cjlogistics", "1234").is_none());
+    }
+
+    #[test]
+    fn disabled_without_ttl() {
+        let cache = SearchCache::new(None);
+        cache.insert("kr.cjlogistics", "1234", info("1234"));
+        assert!(cache.get("kr.cjlogistics", "1234").is_none());
+    }
+
+    #[test]
+    fn survives_a_poisoned_lock() {
+        let cache = SearchCache::new(Some(Duration::from_secs(60)));
+        let _ = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+            let _entries = cache.entries.write().unwrap();
+            panic!("poison the lock");
+        }));
+        cache.insert("kr.cjlogistics", "1234", info("1234"));
+        assert!(cache.get("kr.cjlogistics", "1234").is_some());
+    }
+}
@@ -0,0 +1,564 @@
+use std::{
+    collections::HashMap,
+    net::{IpAddr, SocketAddr},
+    sync::{Arc, Mutex},
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use couriers::{TrackingDetail, TrackingInfo};
+use futures::future::join_all;
+use hmac::{Hmac, Mac};
+use reqwest::Url;
+use serde::{Deserialize, Serialize};
+use sha2::Sha256;
+use tokio::{sync::watch, task::JoinHandle};
+use tracing::{error, info, warn};
+
+use crate::{
+    config::CallbacksConfig,
+    metrics,
+    store::{Subscription, SubscriptionStore},
+};
+
+pub const SIGNATURE_HEADER: &str = "x-tracker-signature";
+pub const TIMESTAMP_HEADER: &str = "x-tracker-timestamp";
+const ATTEMPTS_PER_SUBSCRIPTION: usize = 50;
+const PENDING_PER_SUBSCRIPTION: usize = 50;
+
+type SubscriptionKey = (String, String, String);
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct CallbackPayload {
+    pub user_id: String,
+    pub courier_id: String,
+    pub tracking_number: String,
+    pub status: String,
+    pub is_delivered: bool,
+    pub events: Vec<TrackingDetail>,
+}
+
+impl CallbackPayload {
+    pub fn new(user_id: &str, info: &TrackingInfo, events: Vec<TrackingDetail>) -> Self {
+        Self {
+            user_id: user_id.to_string(),
+            courier_id: info.id.clone(),
+            tracking_number: info.tracking_number.clone(),
+            status: normalized_status(info).to_string(),
+            is_delivered: info.is_delivered,
+            events,
+        }
+    }
+}
+
+pub fn normalized_status(info: &TrackingInfo) -> &'static str {
+    if info.is_delivered {
+        "delivered"
+    } else if info.tracks.is_empty() {
+        "pending"
+    } else {
+        "in_transit"
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct CallbackAttempt {
+    pub url: String,
+    pub attempt: u32,
+    pub attempted_at: i64,
+    pub status_code: Option<u16>,
+    pub error: Option<String>,
+}
+
+/// Queues the payload in the subscription's outbox, dropping the oldest one when full.
+pub fn enqueue(subscription: &mut Subscription, payload: CallbackPayload) {
+    subscription.pending_callbacks.push(payload);
+    if subscription.pending_callbacks.len() > PENDING_PER_SUBSCRIPTION {
+        warn!(
+            courier_id = %subscription.courier_id,
+            "Callback outbox is full, dropping the oldest payload"
+        );
+        subscription.pending_callbacks.remove(0);
+    }
+}
+
+/// Delivers the callback outbox kept in the subscription store.
+///
+/// Payloads leave the outbox only once the callback url accepted them,
+/// so anything undelivered at shutdown is retried after a restart.
+pub struct CallbackDispatcher {
+    config: CallbacksConfig,
+    client: reqwest::Client,
+    store: Arc<SubscriptionStore>,
+    tasks: Arc<Mutex<HashMap<SubscriptionKey, JoinHandle<()>>>>,
+    closing: watch::Sender<bool>,
+    closed: watch::Receiver<bool>,
+}
+
+impl CallbackDispatcher {
+    pub fn new(config: CallbacksConfig, store: Arc<SubscriptionStore>) -> Self {
+        let (closing, closed) = watch::channel(false);
+        Self {
+            config,
+            client: client_builder()
+                .build()
+                .expect("Failed to build callback HTTP client"),
+            store,
+            tasks: Arc::default(),
+            closing,
+            closed,
+        }
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.config.secret.is_some()
+    }
+
+    /// Rejects urls that would make the server call into its own network,
+    /// unless the host is listed in `allowed_hosts`.
+    pub fn check_url(&self, url: &str) -> Result<(), &'static str> {
+        let url = match Url::parse(url) {
+            Ok(url) if url.scheme() == "http" || url.scheme() == "https" => url,
+            _ => return Err("Invalid callback url"),
+        };
+        let host = url.host_str().ok_or("Invalid callback url")?;
+        if !self.config.allowed_hosts.is_empty() {
+            return if is_allowed_host(&self.config.allowed_hosts, host) {
+                Ok(())
+            } else {
+                Err("Callback host is not allowed")
+            };
+        }
+
+        let host = host.trim_start_matches('[').trim_end_matches(']');
+        let is_public = match host.parse::<IpAddr>() {
+            Ok(ip) => is_public_address(ip),
+            Err(_) => {
+                let domain = host.trim_end_matches('.').to_ascii_lowercase();
+                domain != "localhost" && !domain.ends_with(".localhost")
+            }
+        };
+        if is_public {
+            Ok(())
+        } else {
+            Err("Callback host must be a public address")
+        }
+    }
+
+    pub fn attempts(
+        &self,
+        user_id: &str,
+        courier_id: &str,
+        tracking_number: &str,
+    ) -> Vec<CallbackAttempt> {
+        self.store
+            .get(user_id, courier_id, tracking_number)
+            .map(|subscription| subscription.callback_attempts)
+            .unwrap_or_default()
+    }
+
+    /// Starts delivering the outbox of every subscription that isn't being delivered already.
+    pub fn dispatch_pending(&self) {
+        let secret = match &self.config.secret {
+            Some(secret) => secret.clone(),
+            None => return,
+        };
+        if *self.closed.borrow() {
+            return;
+        }
+        // Held while spawning, so a finished task can't remove itself before it is inserted.
+        let mut tasks = match self.tasks.lock() {
+            Ok(tasks) => tasks,
+            Err(_) => return,
+        };
+        for (user_id, subscription) in self.store.all() {
+            let url = match subscription.callback_url {
+                Some(url) if !subscription.pending_callbacks.is_empty() => url,
+                _ => continue,
+            };
+            let key = (
+                user_id,
+                subscription.courier_id,
+                subscription.tracking_number,
+            );
+            if tasks.contains_key(&key) {
+                continue;
+            }
+
+            let delivery = Delivery {
+                key: key.clone(),
+                url,
+                secret: secret.clone(),
+                client: self.client.clone(),
+                config: self.config.clone(),
+                store: self.store.clone(),
+                tasks: self.tasks.clone(),
+                closed: self.closed.clone(),
+            };
+            tasks.insert(key, tokio::spawn(delivery.run()));
+        }
+    }
+
+    /// Stops retrying and waits for the deliveries in flight, whatever is left stays queued.
+    pub async fn drain(&self) {
+        self.closing.send(true).ok();
+        let tasks: Vec<_> = match self.tasks.lock() {
+            Ok(mut tasks) => tasks.drain().map(|(_, task)| task).collect(),
+            Err(_) => return,
+        };
+        join_all(tasks).await;
+    }
+}
+
+struct Delivery {
+    key: SubscriptionKey,
+    url: String,
+    secret: String,
+    client: reqwest::Client,
+    config: CallbacksConfig,
+    store: Arc<SubscriptionStore>,
+    tasks: Arc<Mutex<HashMap<SubscriptionKey, JoinHandle<()>>>>,
+    closed: watch::Receiver<bool>,
+}
+
+impl Delivery {
+    async fn run(mut self) {
+        let (user_id, courier_id, tracking_number) = self.key.clone();
+        while let Some(payload) = self
+            .store
+            .get(&user_id, &courier_id, &tracking_number)
+            .and_then(|subscription| subscription.pending_callbacks.into_iter().next())
+        {
+            if !self.deliver(&payload).await {
+                break;
+            }
+            // The poller may have dropped it from a full outbox meanwhile.
+            self.store
+                .update(&user_id, &courier_id, &tracking_number, |subscription| {
+                    let pending = &mut subscription.pending_callbacks;
+                    if let Some(index) = pending.iter().position(|queued| *queued == payload) {
+                        pending.remove(index);
+                    }
+                });
+        }
+
+        if let Err(err) = self.store.flush() {
+            error!("Failed to flush subscription store: {}", err);
+        }
+        if let Ok(mut tasks) = self.tasks.lock() {
+            tasks.remove(&self.key);
+        }
+    }
+
+    /// Sends the payload, retrying with exponential backoff until it is accepted.
+    async fn deliver(&mut self, payload: &CallbackPayload) -> bool {
+        let body = match serde_json::to_vec(payload) {
+            Ok(body) => body,
+            Err(err) => {
+                error!("Failed to serialize callback payload: {}", err);
+                return false;
+            }
+        };
+
+        let mut backoff = self.config.initial_backoff();
+        for attempt in 1..=self.config.max_attempts {
+            let (status_code, error) = self.send(&body).await;
+            let success = error.is_none();
+            metrics::CALLBACK_DELIVERIES
+                .with_label_values(&[if success { "success" } else { "failure" }])
+                .inc();
+            self.record(CallbackAttempt {
+                url: self.url.clone(),
+                attempt,
+                attempted_at: unix_now(),
+                status_code,
+                error,
+            });
+
+            if success {
+                info!(url = %self.url, attempt, "Delivered callback");
+                return true;
+            }
+            if attempt < self.config.max_attempts {
+                tokio::select! {
+                    _ = tokio::time::sleep(backoff) => {}
+                    _ = self.closed.changed() => return false,
+                }
+                backoff *= 2;
+            }
+        }
+        warn!(url = %self.url, attempts = self.config.max_attempts, "Gave up delivering callback");
+        false
+    }
+
+    async fn send(&self, body: &[u8]) -> (Option<u16>, Option<String>) {
+        let client = match self.pinned_client().await {
+            Ok(client) => client,
+            Err(err) => return (None, Some(err.to_string())),
+        };
+
+        let timestamp = unix_now();
+        let result = client
+            .post(&self.url)
+            .header(reqwest::header::CONTENT_TYPE, "application/json")
+            .header(TIMESTAMP_HEADER, timestamp.to_string())
+            .header(SIGNATURE_HEADER, sign(&self.secret, timestamp, body))
+            .timeout(self.config.timeout())
+            .body(body.to_vec())
+            .send()
+            .await;
+        match result {
+            Ok(response) if response.status().is_success() => {
+                (Some(response.status().as_u16()), None)
+            }
+            Ok(response) => (
+                Some(response.status().as_u16()),
+                Some(format!("Unexpected status {}", response.status())),
+            ),
+            Err(err) => (None, Some(err.to_string())),
+        }
+    }
+
+    /// A client that only reaches the address checked here, resolved again on every attempt
+    /// since a public name can later point inside. The host is pinned to that address so
+    /// reqwest's own lookup can't be rebound, and redirects are never followed.
+    async fn pinned_client(&self) -> Result<reqwest::Client, &'static str> {
+        let url = Url::parse(&self.url).map_err(|_| "Invalid callback url")?;
+        let (host, port) = match (url.host_str(), url.port_or_known_default()) {
+            (Some(host), Some(port)) => (host.to_string(), port),
+            _ => return Err("Invalid callback url"),
+        };
+        if is_allowed_host(&self.config.allowed_hosts, &host) {
+            return Ok(self.client.clone());
+        }
+
+        let host = host.trim_start_matches('[').trim_end_matches(']');
+        let addresses: Vec<SocketAddr> = tokio::net::lookup_host((host, port))
+            .await
+            .map_err(|_| "Can't resolve callback host")?
+            .collect();
+        let address = match addresses.first() {
+            Some(address)
+                if addresses
+                    .iter()
+                    .all(|address| is_public_address(address.ip())) =>
+            {
+                *address
+            }
+            _ => return Err("Callback host resolves to a non-public address"),
+        };
+        if host.parse::<IpAddr>().is_ok() {
+            return Ok(self.client.clone());
+        }
+        client_builder()
+            .resolve(host, address)
+            .build()
+            .map_err(|_| "Failed to build callback HTTP client")
+    }
+
+    fn record(&self, attempt: CallbackAttempt) {
+        let (user_id, courier_id, tracking_number) = &self.key;
+        self.store
+            .update(user_id, courier_id, tracking_number, |subscription| {
+                subscription.callback_attempts.push(attempt);
+                if subscription.callback_attempts.len() > ATTEMPTS_PER_SUBSCRIPTION {
+                    subscription.callback_attempts.remove(0);
+                }
+            });
+    }
+}
+
+/// Redirects could lead a public callback url to an internal address, so none are followed.
+fn client_builder() -> reqwest::ClientBuilder {
+    reqwest::Client::builder().redirect(reqwest::redirect::Policy::none())
+}
+
+/// Hex encoded HMAC-SHA256 of `v1:{timestamp}:{body}`, sent as `v1=<hex>`.
+///
+/// The timestamp travels in [`TIMESTAMP_HEADER`] so receivers can refuse replayed requests.
+pub(crate) fn sign(secret: &str, timestamp: i64, body: &[u8]) -> String {
+    let mut mac =
+        Hmac::<Sha256>::new_from_slice(secret.as_bytes()).expect("HMAC accepts keys of any size");
+    mac.update(format!("v1:{}:", timestamp).as_bytes());
+    mac.update(body);
+    format!("v1={}", hex::encode(mac.finalize().into_bytes()))
+}
+
+fn is_allowed_host(allowed_hosts: &[String], host: &str) -> bool {
+    allowed_hosts
+        .iter()
+        .any(|allowed| allowed.eq_ignore_ascii_case(host))
+}
+
+fn is_public_address(ip: IpAddr) -> bool {
+    match ip {
+        IpAddr::V4(ip) => {
+            let [a, b, ..] = ip.octets();
+            !(ip.is_private()
+                || ip.is_loopback()
+                || ip.is_link_local()
+                || ip.is_broadcast()
+                || ip.is_documentation()
+                || ip.is_unspecified()
+                || ip.is_multicast()
+                // Carrier-grade NAT, 100.64.0.0/10
+                || (a == 100 && (b & 0xc0) == 64))
+        }
+        IpAddr::V6(ip) => {
+            let segments = ip.segments();
+            if segments[..5] == [0; 5] && segments[5] == 0xffff {
+                if let Some(ip) = ip.to_ipv4() {
+                    return is_public_address(IpAddr::V4(ip));
+                }
+            }
+            !(ip.is_loopback()
+                || ip.is_unspecified()
+                || ip.is_multicast()
+                // Unique local, fc00::/7
+                || (segments[0] & 0xfe00) == 0xfc00
+                // Link-local, fe80::/10
+                || (segments[0] & 0xffc0) == 0xfe80)
+        }
+    }
+}
+
+fn unix_now() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|duration| duration.as_secs() as i64)
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        convert::Infallible,
+        sync::atomic::{AtomicUsize, Ordering},
+    };
+
+    use hyper::{
+        header::LOCATION,
+        service::{make_service_fn, service_fn},
+        Body, Request, Response, Server,
+    };
+
+    use super::*;
+
+    fn dispatcher(allowed_hosts: &[&str]) -> CallbackDispatcher {
+        let config = CallbacksConfig {
+            allowed_hosts: allowed_hosts.iter().map(|host| host.to_string()).collect(),
+            ..CallbacksConfig::default()
+        };
+        CallbackDispatcher::new(config, Arc::default())
+    }
+
+    #[test]
+    fn signs_timestamp_and_body() {
+        assert_eq!(
+            sign("secret", 1700000000, br#"{"a":1}"#),
+            "v1=17bb75ebc8e3db9f892e182dac08b5bd115c2a12570908248a7ed41d89710ec8"
+        );
+        assert_ne!(
+            sign("secret", 1700000001, br#"{"a":1}"#),
+            sign("secret", 1700000000, br#"{"a":1}"#)
+        );
+    }
+
+    #[test]
+    fn rejects_private_hosts() {
+        let dispatcher = dispatcher(&[]);
+        for url in [
+            "http://127.0.0.1/hook",
+            "http://10.0.0.8/hook",
+            "http://192.168.1.1/hook",
+            "http://169.254.169.254/latest/meta-data",
+            "http://100.64.0.1/hook",
+            "http://[::1]/hook",
+            "http://[fe80::1]/hook",
+            "http://[fd00::1]/hook",
+            "http://[::ffff:127.0.0.1]/hook",
+            "http://localhost:8080/hook",
+            "http://api.localhost/hook",
+        ] {
+            assert!(dispatcher.check_url(url).is_err(), "{}", url);
+        }
+    }
+
+    #[test]
+    fn accepts_public_hosts() {
+        let dispatcher = dispatcher(&[]);
+        assert!(dispatcher.check_url("https://example.com/hook").is_ok());
+        assert!(dispatcher.check_url("http://93.184.216.34/hook").is_ok());
+        assert!(dispatcher.check_url("ftp://example.com/hook").is_err());
+    }
+
+    #[test]
+    fn allowed_hosts_replace_the_public_check() {
+        let dispatcher = dispatcher(&["hooks.internal"]);
+        assert!(dispatcher.check_url("http://hooks.internal/hook").is_ok());
+        assert!(dispatcher.check_url("https://example.com/hook").is_err());
+    }
+
+    /// Answers every request with `status` and `location`, counting the requests.
+    fn spawn_server(status: u16, location: Option<String>) -> (SocketAddr, Arc<AtomicUsize>) {
+        let hits = Arc::new(AtomicUsize::new(0));
+        let counter = hits.clone();
+        let make_service = make_service_fn(move |_| {
+            let counter = counter.clone();
+            let location = location.clone();
+            async move {
+                Ok::<_, Infallible>(service_fn(move |_: Request<Body>| {
+                    counter.fetch_add(1, Ordering::SeqCst);
+                    let mut response = Response::builder().status(status);
+                    if let Some(location) = &location {
+                        response = response.header(LOCATION, location.as_str());
+                    }
+                    async move { Ok::<_, Infallible>(response.body(Body::empty()).unwrap()) }
+                }))
+            }
+        });
+        let server = Server::bind(&([127, 0, 0, 1], 0).into()).serve(make_service);
+        let address = server.local_addr();
+        tokio::spawn(server);
+        (address, hits)
+    }
+
+    fn delivery(dispatcher: &CallbackDispatcher, url: String) -> Delivery {
+        Delivery {
+            key: SubscriptionKey::default(),
+            url,
+            secret: "secret".to_string(),
+            client: dispatcher.client.clone(),
+            config: dispatcher.config.clone(),
+            store: dispatcher.store.clone(),
+            tasks: dispatcher.tasks.clone(),
+            closed: dispatcher.closed.clone(),
+        }
+    }
+
+    #[tokio::test]
+    async fn does_not_follow_redirects() {
+        let (internal, internal_hits) = spawn_server(200, None);
+        let (public, public_hits) = spawn_server(302, Some(format!("http://{}/", internal)));
+        let dispatcher = dispatcher(&["127.0.0.1"]);
+
+        let (status_code, error) = delivery(&dispatcher, format!("http://{}/hook", public))
+            .send(b"{}")
+            .await;
+        assert_eq!(status_code, Some(302));
+        assert!(error.is_some());
+        assert_eq!(public_hits.load(Ordering::SeqCst), 1);
+        assert_eq!(internal_hits.load(Ordering::SeqCst), 0);
+    }
+
+    #[tokio::test]
+    async fn refuses_names_resolving_inside() {
+        let (address, hits) = spawn_server(200, None);
+        let dispatcher = dispatcher(&[]);
+
+        let url = format!("http://localhost:{}/hook", address.port());
+        let (status_code, error) = delivery(&dispatcher, url).send(b"{}").await;
+        assert_eq!(status_code, None);
+        assert!(error.is_some());
+        assert_eq!(hits.load(Ordering::SeqCst), 0);
+    }
+}
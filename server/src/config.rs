@@ -0,0 +1,519 @@
+use std::{
+    collections::HashMap,
+    env, fmt, fs, io,
+    net::SocketAddr,
+    path::{Path, PathBuf},
+    str::FromStr,
+    time::Duration,
+};
+
+use serde::Deserialize;
+
+const DEFAULT_CONFIG_PATH: &str = "server.toml";
+
+#[derive(Debug)]
+pub enum ConfigError {
+    Read(PathBuf, io::Error),
+    Parse(PathBuf, toml::de::Error),
+    Env(&'static str, String),
+    Invalid(String),
+}
+
+impl fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ConfigError::Read(path, err) => write!(f, "can't read {}: {}", path.display(), err),
+            ConfigError::Parse(path, err) => write!(f, "can't parse {}: {}", path.display(), err),
+            ConfigError::Env(name, value) => {
+                write!(f, "env {} has invalid value {:?}", name, value)
+            }
+            ConfigError::Invalid(message) => f.write_str(message),
+        }
+    }
+}
+
+impl std::error::Error for ConfigError {}
+
+#[derive(Debug, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Config {
+    pub bind_addr: SocketAddr,
+    pub metrics_addr: Option<SocketAddr>,
+    pub store_path: Option<PathBuf>,
+    pub api_keys_path: Option<PathBuf>,
+    pub legacy_user_namespace: Option<String>,
+    pub health_check_interval_secs: u64,
+    pub log: LogConfig,
+    pub tls: TlsConfig,
+    pub tracker: TrackerConfig,
+    pub callbacks: CallbacksConfig,
+    pub email: EmailConfig,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            bind_addr: ([0, 0, 0, 0], 50051).into(),
+            metrics_addr: None,
+            store_path: None,
+            api_keys_path: None,
+            legacy_user_namespace: None,
+            health_check_interval_secs: 10,
+            log: LogConfig::default(),
+            tls: TlsConfig::default(),
+            tracker: TrackerConfig::default(),
+            callbacks: CallbacksConfig::default(),
+            email: EmailConfig::default(),
+        }
+    }
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct LogConfig {
+    pub format: LogFormat,
+    pub mask_tracking_numbers: bool,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LogFormat {
+    Text,
+    Json,
+}
+
+impl Default for LogFormat {
+    fn default() -> Self {
+        LogFormat::Text
+    }
+}
+
+impl FromStr for LogFormat {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "text" => Ok(LogFormat::Text),
+            "json" => Ok(LogFormat::Json),
+            _ => Err(()),
+        }
+    }
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct TlsConfig {
+    pub cert_path: Option<PathBuf>,
+    pub key_path: Option<PathBuf>,
+    pub client_ca_path: Option<PathBuf>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct TrackerConfig {
+    pub request_timeout_secs: u64,
+    pub cache_ttl_secs: u64,
+    pub couriers: HashMap<String, CourierConfig>,
+}
+
+impl Default for TrackerConfig {
+    fn default() -> Self {
+        Self {
+            request_timeout_secs: 10,
+            cache_ttl_secs: 0,
+            couriers: HashMap::new(),
+        }
+    }
+}
+
+impl TrackerConfig {
+    pub fn is_enabled(&self, courier_id: &str) -> bool {
+        self.couriers
+            .get(courier_id)
+            .map(|courier| courier.enabled)
+            .unwrap_or(true)
+    }
+
+    pub fn timeout(&self, courier_id: &str) -> Duration {
+        let secs = self
+            .couriers
+            .get(courier_id)
+            .and_then(|courier| courier.timeout_secs)
+            .unwrap_or(self.request_timeout_secs);
+        Duration::from_secs(secs)
+    }
+
+    pub fn cache_ttl(&self) -> Option<Duration> {
+        match self.cache_ttl_secs {
+            0 => None,
+            secs => Some(Duration::from_secs(secs)),
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct CourierConfig {
+    pub enabled: bool,
+    pub timeout_secs: Option<u64>,
+}
+
+impl Default for CourierConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            timeout_secs: None,
+        }
+    }
+}
+
+#[derive(Clone, Debug, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct CallbacksConfig {
+    /// Key used to sign callback payloads, callbacks are refused while unset.
+    pub secret: Option<String>,
+    pub poll_interval_secs: u64,
+    pub timeout_secs: u64,
+    pub max_attempts: u32,
+    pub initial_backoff_secs: u64,
+    /// Only these hosts may receive callbacks when set, otherwise any public host may.
+    pub allowed_hosts: Vec<String>,
+}
+
+impl Default for CallbacksConfig {
+    fn default() -> Self {
+        Self {
+            secret: None,
+            poll_interval_secs: 300,
+            timeout_secs: 10,
+            max_attempts: 5,
+            initial_backoff_secs: 1,
+            allowed_hosts: Vec::new(),
+        }
+    }
+}
+
+impl CallbacksConfig {
+    pub fn poll_interval(&self) -> Duration {
+        Duration::from_secs(self.poll_interval_secs)
+    }
+
+    pub fn timeout(&self) -> Duration {
+        Duration::from_secs(self.timeout_secs)
+    }
+
+    pub fn initial_backoff(&self) -> Duration {
+        Duration::from_secs(self.initial_backoff_secs)
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum EmailTransport {
+    Smtp,
+    /// Writes each message as an `.eml` file into `sink_dir` instead of sending it.
+    File,
+}
+
+impl Default for EmailTransport {
+    fn default() -> Self {
+        EmailTransport::Smtp
+    }
+}
+
+impl FromStr for EmailTransport {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "smtp" => Ok(EmailTransport::Smtp),
+            "file" => Ok(EmailTransport::File),
+            _ => Err(()),
+        }
+    }
+}
+
+#[derive(Clone, Debug, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct EmailConfig {
+    /// Sender mailbox, email notifications are refused while unset.
+    pub from: Option<String>,
+    pub transport: EmailTransport,
+    pub smtp_host: Option<String>,
+    pub smtp_port: u16,
+    pub smtp_username: Option<String>,
+    pub smtp_password: Option<String>,
+    pub smtp_starttls: bool,
+    pub sink_dir: PathBuf,
+    pub subject_template: Option<String>,
+    pub text_template_path: Option<PathBuf>,
+    pub html_template_path: Option<PathBuf>,
+}
+
+impl Default for EmailConfig {
+    fn default() -> Self {
+        Self {
+            from: None,
+            transport: EmailTransport::default(),
+            smtp_host: None,
+            smtp_port: 587,
+            smtp_username: None,
+            smtp_password: None,
+            smtp_starttls: true,
+            sink_dir: PathBuf::from("mail"),
+            subject_template: None,
+            text_template_path: None,
+            html_template_path: None,
+        }
+    }
+}
+
+impl Config {
+    /// Reads `CONFIG_PATH` (or `server.toml` if it exists), then applies env overrides.
+    pub fn load() -> Result<Self, ConfigError> {
+        let mut config = match env_var("CONFIG_PATH") {
+            Some(path) => Self::from_file(path)?,
+            None if Path::new(DEFAULT_CONFIG_PATH).exists() => {
+                Self::from_file(DEFAULT_CONFIG_PATH)?
+            }
+            None => Self::default(),
+        };
+        config.apply_env()?;
+        config.validate()?;
+        Ok(config)
+    }
+
+    pub fn from_file<P: AsRef<Path>>(path: P) -> Result<Self, ConfigError> {
+        let path = path.as_ref().to_path_buf();
+        let content =
+            fs::read_to_string(&path).map_err(|err| ConfigError::Read(path.clone(), err))?;
+        toml::from_str(&content).map_err(|err| ConfigError::Parse(path, err))
+    }
+
+    fn apply_env(&mut self) -> Result<(), ConfigError> {
+        if let Some(addr) = parse_env("BIND_ADDR")? {
+            self.bind_addr = addr;
+        }
+        if let Some(addr) = parse_env("METRICS_ADDR")? {
+            self.metrics_addr = Some(addr);
+        }
+        if let Some(path) = env_var("STORE_PATH") {
+            self.store_path = Some(path.into());
+        }
+        if let Some(path) = env_var("API_KEYS_PATH") {
+            self.api_keys_path = Some(path.into());
+        }
+        if let Some(namespace) = env_var("LEGACY_USER_NAMESPACE") {
+            self.legacy_user_namespace = Some(namespace);
+        }
+        if let Some(format) = parse_env("LOG_FORMAT")? {
+            self.log.format = format;
+        }
+        if let Some(value) = env_var("LOG_MASK_TRACKING_NUMBERS") {
+            self.log.mask_tracking_numbers = value == "true" || value == "1";
+        }
+        if let Some(path) = env_var("TLS_CERT_PATH") {
+            self.tls.cert_path = Some(path.into());
+        }
+        if let Some(path) = env_var("TLS_KEY_PATH") {
+            self.tls.key_path = Some(path.into());
+        }
+        if let Some(path) = env_var("TLS_CLIENT_CA_PATH") {
+            self.tls.client_ca_path = Some(path.into());
+        }
+        if let Some(secs) = parse_env("COURIER_TIMEOUT_SECS")? {
+            self.tracker.request_timeout_secs = secs;
+        }
+        if let Some(secs) = parse_env("CACHE_TTL_SECS")? {
+            self.tracker.cache_ttl_secs = secs;
+        }
+        if let Some(secret) = env_var("CALLBACK_SECRET") {
+            self.callbacks.secret = Some(secret);
+        }
+        if let Some(secs) = parse_env("POLL_INTERVAL_SECS")? {
+            self.callbacks.poll_interval_secs = secs;
+        }
+        if let Some(from) = env_var("EMAIL_FROM") {
+            self.email.from = Some(from);
+        }
+        if let Some(transport) = parse_env("EMAIL_TRANSPORT")? {
+            self.email.transport = transport;
+        }
+        if let Some(host) = env_var("SMTP_HOST") {
+            self.email.smtp_host = Some(host);
+        }
+        if let Some(port) = parse_env("SMTP_PORT")? {
+            self.email.smtp_port = port;
+        }
+        if let Some(username) = env_var("SMTP_USERNAME") {
+            self.email.smtp_username = Some(username);
+        }
+        if let Some(password) = env_var("SMTP_PASSWORD") {
+            self.email.smtp_password = Some(password);
+        }
+        if let Some(dir) = env_var("EMAIL_SINK_DIR") {
+            self.email.sink_dir = dir.into();
+        }
+        if let Some(ids) = env_var("DISABLED_COURIERS") {
+            for id in ids.split(',').map(str::trim).filter(|id| !id.is_empty()) {
+                self.tracker
+                    .couriers
+                    .entry(id.to_string())
+                    .or_default()
+                    .enabled = false;
+            }
+        }
+        Ok(())
+    }
+
+    fn validate(&self) -> Result<(), ConfigError> {
+        if self.tls.cert_path.is_some() != self.tls.key_path.is_some() {
+            return Err(ConfigError::Invalid(
+                "tls.cert_path and tls.key_path must be set together".to_string(),
+            ));
+        }
+        if self.tls.client_ca_path.is_some() && self.tls.cert_path.is_none() {
+            return Err(ConfigError::Invalid(
+                "tls.client_ca_path requires tls.cert_path".to_string(),
+            ));
+        }
+        if self.health_check_interval_secs == 0 {
+            return Err(ConfigError::Invalid(
+                "health_check_interval_secs must be greater than 0".to_string(),
+            ));
+        }
+        if self.tracker.request_timeout_secs == 0 {
+            return Err(ConfigError::Invalid(
+                "tracker.request_timeout_secs must be greater than 0".to_string(),
+            ));
+        }
+        if self.callbacks.poll_interval_secs == 0
+            || self.callbacks.timeout_secs == 0
+            || self.callbacks.max_attempts == 0
+        {
+            return Err(ConfigError::Invalid(
+                "callbacks.poll_interval_secs, timeout_secs and max_attempts must be greater than 0"
+                    .to_string(),
+            ));
+        }
+        if let Some(from) = &self.email.from {
+            if from.parse::<lettre::message::Mailbox>().is_err() {
+                return Err(ConfigError::Invalid(format!(
+                    "email.from {:?} is not a valid mailbox",
+                    from
+                )));
+            }
+            if self.email.transport == EmailTransport::Smtp && self.email.smtp_host.is_none() {
+                return Err(ConfigError::Invalid(
+                    "email.smtp_host is required for the smtp transport".to_string(),
+                ));
+            }
+        }
+        if self.email.smtp_username.is_some() != self.email.smtp_password.is_some() {
+            return Err(ConfigError::Invalid(
+                "email.smtp_username and email.smtp_password must be set together".to_string(),
+            ));
+        }
+        for (id, courier) in &self.tracker.couriers {
+            if !couriers::is_supported(id) {
+                return Err(ConfigError::Invalid(format!("unknown courier {:?}", id)));
+            }
+            if courier.timeout_secs == Some(0) {
+                return Err(ConfigError::Invalid(format!(
+                    "tracker.couriers.{:?}.timeout_secs must be greater than 0",
+                    id
+                )));
+            }
+        }
+        Ok(())
+    }
+}
+
+fn env_var(name: &str) -> Option<String> {
+    env::var(name).ok().filter(|value| !value.is_empty())
+}
+
+fn parse_env<T: FromStr>(name: &'static str) -> Result<Option<T>, ConfigError> {
+    match env_var(name) {
+        Some(value) => value
+            .parse()
+            .map(Some)
+            .map_err(|_| ConfigError::Env(name, value)),
+        None => Ok(None),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(content: &str) -> Config {
+        toml::from_str(content).unwrap()
+    }
+
+    #[test]
+    fn applies_courier_settings() {
+        let config = parse(
+            r#"
+            [tracker]
+            request_timeout_secs = 10
+
+            [tracker.couriers."kr.cupost"]
+            enabled = false
+
+            [tracker.couriers."us.fedex"]
+            timeout_secs = 20
+            "#,
+        );
+        assert!(config.validate().is_ok());
+
+        let tracker = &config.tracker;
+        assert!(!tracker.is_enabled("kr.cupost"));
+        assert!(tracker.is_enabled("us.fedex"));
+        assert!(tracker.is_enabled("kr.hanjin"));
+        assert_eq!(tracker.timeout("us.fedex"), Duration::from_secs(20));
+        assert_eq!(tracker.timeout("kr.cupost"), Duration::from_secs(10));
+        assert_eq!(tracker.timeout("kr.hanjin"), Duration::from_secs(10));
+    }
+
+    #[test]
+    fn rejects_invalid_courier_settings() {
+        let unknown = parse("[tracker.couriers.\"kr.unknown\"]\nenabled = false");
+        assert!(unknown.validate().is_err());
+
+        let zero_timeout = parse("[tracker.couriers.\"us.fedex\"]\ntimeout_secs = 0");
+        assert!(zero_timeout.validate().is_err());
+
+        assert!(toml::from_str::<Config>("[tracker.couriers.\"us.fedex\"]\ntimeout = 5").is_err());
+    }
+
+    #[test]
+    fn validates_settings_that_go_together() {
+        assert!(Config::default().validate().is_ok());
+        assert!(parse("[tls]\ncert_path = \"cert.pem\"").validate().is_err());
+        assert!(parse("[tls]\nclient_ca_path = \"ca.pem\"")
+            .validate()
+            .is_err());
+        assert!(parse("[callbacks]\nmax_attempts = 0").validate().is_err());
+        assert!(parse("[email]\nfrom = \"not a mailbox\"")
+            .validate()
+            .is_err());
+        assert!(parse("[email]\nfrom = \"tracker@example.com\"")
+            .validate()
+            .is_err());
+        assert!(
+            parse("[email]\nfrom = \"tracker@example.com\"\ntransport = \"file\"")
+                .validate()
+                .is_ok()
+        );
+    }
+
+    #[test]
+    fn turns_off_caching_with_zero_ttl() {
+        assert_eq!(TrackerConfig::default().cache_ttl(), None);
+        assert_eq!(
+            parse("[tracker]\ncache_ttl_secs = 60").tracker.cache_ttl(),
+            Some(Duration::from_secs(60))
+        );
+    }
+}
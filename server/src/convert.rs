@@ -0,0 +1,50 @@
+use couriers::{CourierContact, CourierInfo, TrackingDetail, TrackingInfo};
+
+use crate::tracker;
+
+impl From<CourierInfo> for tracker::SupportCouriersDetail {
+    fn from(courier: CourierInfo) -> Self {
+        Self {
+            id: courier.id.to_string(),
+            name: courier.name.to_string(),
+        }
+    }
+}
+
+impl From<TrackingInfo> for tracker::TrackingInfo {
+    fn from(info: TrackingInfo) -> Self {
+        Self {
+            id: info.id,
+            name: info.name,
+            url: info.url,
+            tracking_number: info.tracking_number,
+            is_delivered: info.is_delivered,
+            sender: info.sender,
+            receiver: info.receiver,
+            product: info.product,
+            tracks: info.tracks.into_iter().map(Into::into).collect(),
+        }
+    }
+}
+
+impl From<TrackingDetail> for tracker::TrackingDetail {
+    fn from(detail: TrackingDetail) -> Self {
+        Self {
+            time: detail.time,
+            message: detail.message,
+            status: detail.status,
+            location: detail.location,
+            live_tracking_url: detail.live_tracking_url,
+            courier_contact: detail.courier_contact.map(Into::into),
+        }
+    }
+}
+
+impl From<CourierContact> for tracker::CourierContact {
+    fn from(contact: CourierContact) -> Self {
+        Self {
+            name: contact.name,
+            phone: contact.phone,
+        }
+    }
+}
@@ -1,3 +0,0 @@
-pub mod cn;
-pub mod kr;
-pub mod us;
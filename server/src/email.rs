@@ -0,0 +1,338 @@
+use std::fs;
+
+use chrono::DateTime;
+use couriers::{TrackingDetail, TrackingInfo};
+use lettre::{
+    message::{Mailbox, MultiPart},
+    transport::smtp::authentication::Credentials,
+    AsyncFileTransport, AsyncSmtpTransport, AsyncTransport, Message, Tokio1Executor,
+};
+use serde::Serialize;
+use tinytemplate::TinyTemplate;
+use tracing::{info, warn};
+
+use crate::{
+    callback::normalized_status,
+    config::{EmailConfig, EmailTransport},
+    logging, metrics,
+};
+
+const DEFAULT_SUBJECT_TEMPLATE: &str = "[{status}] {title}";
+const DEFAULT_TEXT_TEMPLATE: &str = include_str!("../templates/email.txt");
+const DEFAULT_HTML_TEMPLATE: &str = include_str!("../templates/email.html");
+
+#[derive(Serialize)]
+pub struct EmailContext {
+    title: String,
+    courier_name: String,
+    tracking_number: String,
+    url: String,
+    status: &'static str,
+    is_delivered: bool,
+    events: Vec<EmailEvent>,
+}
+
+#[derive(Serialize)]
+struct EmailEvent {
+    time: String,
+    summary: String,
+}
+
+impl EmailContext {
+    pub fn new(label: Option<&str>, info: &TrackingInfo, events: &[TrackingDetail]) -> Self {
+        let title = match label {
+            Some(label) => label.to_string(),
+            None => format!("{} {}", info.name, info.tracking_number),
+        };
+        Self {
+            title,
+            courier_name: info.name.clone(),
+            tracking_number: info.tracking_number.clone(),
+            url: info.url.clone(),
+            status: normalized_status(info),
+            is_delivered: info.is_delivered,
+            events: events
+                .iter()
+                .map(|event| EmailEvent {
+                    time: format_time(&event.time),
+                    summary: [&event.status, &event.location, &event.message]
+                        .iter()
+                        .filter_map(|part| part.as_deref())
+                        .filter(|part| !part.is_empty())
+                        .collect::<Vec<_>>()
+                        .join(" · "),
+                })
+                .collect(),
+        }
+    }
+}
+
+/// Keeps the courier's own offset, recipients may not share the server's time zone.
+fn format_time(time: &str) -> String {
+    DateTime::parse_from_rfc3339(time)
+        .map(|datetime| datetime.format("%Y-%m-%d %H:%M (UTC%:z)").to_string())
+        .unwrap_or_else(|_| time.to_string())
+}
+
+enum Transport {
+    Smtp(AsyncSmtpTransport<Tokio1Executor>),
+    File(AsyncFileTransport<Tokio1Executor>),
+}
+
+struct Templates {
+    subject: String,
+    text: String,
+    html: String,
+}
+
+impl Templates {
+    fn render(&self, context: &EmailContext) -> Result<(String, String, String), String> {
+        let mut plain = TinyTemplate::new();
+        plain.set_default_formatter(&tinytemplate::format_unescaped);
+        plain
+            .add_template("subject", &self.subject)
+            .map_err(|err| format!("subject template: {}", err))?;
+        plain
+            .add_template("text", &self.text)
+            .map_err(|err| format!("text template: {}", err))?;
+        let mut html = TinyTemplate::new();
+        html.add_template("html", &self.html)
+            .map_err(|err| format!("html template: {}", err))?;
+
+        Ok((
+            plain
+                .render("subject", context)
+                .map_err(|err| err.to_string())?,
+            plain
+                .render("text", context)
+                .map_err(|err| err.to_string())?,
+            html.render("html", context)
+                .map_err(|err| err.to_string())?,
+        ))
+    }
+}
+
+#[derive(Default)]
+pub struct EmailNotifier {
+    inner: Option<(Mailbox, Transport, Templates)>,
+}
+
+impl EmailNotifier {
+    pub fn new(config: &EmailConfig) -> Result<Self, String> {
+        let from: Mailbox = match &config.from {
+            Some(from) => from.parse().map_err(|err| format!("email.from: {}", err))?,
+            None => return Ok(Self::default()),
+        };
+
+        let transport = match config.transport {
+            EmailTransport::Smtp => {
+                let host = config
+                    .smtp_host
+                    .as_deref()
+                    .ok_or_else(|| "email.smtp_host is not set".to_string())?;
+                let builder = if config.smtp_starttls {
+                    AsyncSmtpTransport::<Tokio1Executor>::starttls_relay(host)
+                        .map_err(|err| err.to_string())?
+                } else {
+                    AsyncSmtpTransport::<Tokio1Executor>::builder_dangerous(host)
+                };
+                let mut builder = builder.port(config.smtp_port);
+                if let (Some(username), Some(password)) =
+                    (&config.smtp_username, &config.smtp_password)
+                {
+                    builder =
+                        builder.credentials(Credentials::new(username.clone(), password.clone()));
+                }
+                Transport::Smtp(builder.build())
+            }
+            EmailTransport::File => {
+                fs::create_dir_all(&config.sink_dir).map_err(|err| {
+                    format!("can't create {}: {}", config.sink_dir.display(), err)
+                })?;
+                Transport::File(AsyncFileTransport::new(&config.sink_dir))
+            }
+        };
+
+        let read_template = |path: &Option<std::path::PathBuf>, default: &str| match path {
+            Some(path) => fs::read_to_string(path)
+                .map_err(|err| format!("can't read {}: {}", path.display(), err)),
+            None => Ok(default.to_string()),
+        };
+        let templates = Templates {
+            subject: config
+                .subject_template
+                .clone()
+                .unwrap_or_else(|| DEFAULT_SUBJECT_TEMPLATE.to_string()),
+            text: read_template(&config.text_template_path, DEFAULT_TEXT_TEMPLATE)?,
+            html: read_template(&config.html_template_path, DEFAULT_HTML_TEMPLATE)?,
+        };
+        // Surface template syntax errors at startup rather than on the first update.
+        let sample = TrackingInfo {
+            tracks: vec![TrackingDetail::default()],
+            ..TrackingInfo::default()
+        };
+        templates.render(&EmailContext::new(None, &sample, &sample.tracks))?;
+
+        Ok(Self {
+            inner: Some((from, transport, templates)),
+        })
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.inner.is_some()
+    }
+
+    /// Sends a notification, returning whether it was sent or email is disabled.
+    pub async fn send(&self, to: &str, context: &EmailContext) -> bool {
+        let (from, transport, templates) = match &self.inner {
+            Some(inner) => inner,
+            None => return true,
+        };
+        let result = async {
+            let (subject, text, html) = templates.render(context)?;
+            let message = Message::builder()
+                .from(from.clone())
+                .to(to
+                    .parse()
+                    .map_err(|err: lettre::address::AddressError| err.to_string())?)
+                .subject(subject)
+                .multipart(MultiPart::alternative_plain_html(text, html))
+                .map_err(|err| err.to_string())?;
+            match transport {
+                Transport::Smtp(transport) => transport
+                    .send(message)
+                    .await
+                    .map(|_| ())
+                    .map_err(|err| err.to_string()),
+                Transport::File(transport) => transport
+                    .send(message)
+                    .await
+                    .map(|_| ())
+                    .map_err(|err| err.to_string()),
+            }
+        }
+        .await;
+
+        match result {
+            Ok(()) => {
+                metrics::EMAIL_NOTIFICATIONS
+                    .with_label_values(&["success"])
+                    .inc();
+                info!(
+                    tracking_number = %logging::mask_tracking_number(&context.tracking_number),
+                    "Sent email notification"
+                );
+                true
+            }
+            Err(err) => {
+                metrics::EMAIL_NOTIFICATIONS
+                    .with_label_values(&["failure"])
+                    .inc();
+                warn!(error = %err, "Failed to send email notification");
+                false
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn info() -> TrackingInfo {
+        TrackingInfo {
+            name: "CJ".to_string(),
+            url: "https://example.com/track".to_string(),
+            tracking_number: "1234".to_string(),
+            is_delivered: true,
+            tracks: vec![TrackingDetail {
+                time: "2021-01-02T15:04:00+09:00".to_string(),
+                status: Some("Delivered".to_string()),
+                location: Some("Seoul".to_string()),
+                ..TrackingDetail::default()
+            }],
+            ..TrackingInfo::default()
+        }
+    }
+
+    fn templates() -> Templates {
+        Templates {
+            subject: DEFAULT_SUBJECT_TEMPLATE.to_string(),
+            text: DEFAULT_TEXT_TEMPLATE.to_string(),
+            html: DEFAULT_HTML_TEMPLATE.to_string(),
+        }
+    }
+
+    #[test]
+    fn renders_default_templates() {
+        let info = info();
+        let context = EmailContext::new(Some("Shoes"), &info, &info.tracks);
+        let (subject, text, html) = templates().render(&context).unwrap();
+
+        assert_eq!(subject, "[delivered] Shoes");
+        assert!(text.contains("CJ 1234"));
+        assert!(text.contains("The parcel has been delivered."));
+        assert!(text.contains("- 2021-01-02 15:04 (UTC+09:00)  Delivered · Seoul"));
+        assert!(html.contains("<td>Delivered · Seoul</td>"));
+        assert!(html.contains(r#"<a href="https://example.com/track">"#));
+    }
+
+    #[test]
+    fn escapes_only_the_html_part() {
+        let info = info();
+        let context = EmailContext::new(Some("<b>Shoes</b>"), &info, &info.tracks);
+        let (subject, text, html) = templates().render(&context).unwrap();
+
+        assert_eq!(subject, "[delivered] <b>Shoes</b>");
+        assert!(text.starts_with("<b>Shoes</b>"));
+        assert!(html.contains("<h2>&lt;b&gt;Shoes&lt;/b&gt;</h2>"));
+    }
+
+    #[test]
+    fn keeps_the_courier_offset() {
+        assert_eq!(
+            format_time("2021-01-02T15:04:00-05:00"),
+            "2021-01-02 15:04 (UTC-05:00)"
+        );
+        assert_eq!(format_time("yesterday"), "yesterday");
+    }
+
+    #[test]
+    fn rejects_broken_templates() {
+        let config = EmailConfig {
+            from: Some("tracker@example.com".to_string()),
+            transport: EmailTransport::File,
+            sink_dir: std::env::temp_dir(),
+            subject_template: Some("{status".to_string()),
+            ..EmailConfig::default()
+        };
+        assert!(EmailNotifier::new(&config).is_err());
+    }
+
+    #[tokio::test]
+    async fn writes_messages_with_the_file_transport() {
+        let sink_dir =
+            std::env::temp_dir().join(format!("tracker-email-test-{}", std::process::id()));
+        let config = EmailConfig {
+            from: Some("tracker@example.com".to_string()),
+            transport: EmailTransport::File,
+            sink_dir: sink_dir.clone(),
+            ..EmailConfig::default()
+        };
+        let notifier = EmailNotifier::new(&config).unwrap();
+        let info = info();
+        let context = EmailContext::new(Some("Shoes"), &info, &info.tracks);
+        assert!(notifier.send("user@example.com", &context).await);
+        assert!(!notifier.send("not an address", &context).await);
+
+        let messages = fs::read_dir(&sink_dir)
+            .unwrap()
+            .map(|entry| fs::read_to_string(entry.unwrap().path()).unwrap())
+            .collect::<Vec<_>>();
+        fs::remove_dir_all(&sink_dir).ok();
+
+        assert_eq!(messages.len(), 1);
+        assert!(messages[0].contains("Subject: [delivered] Shoes"));
+        assert!(messages[0].contains("To: user@example.com"));
+    }
+}
@@ -0,0 +1,37 @@
+use std::{sync::Arc, time::Duration};
+
+use tonic_health::server::HealthReporter;
+use tracing::{info, warn};
+
+use crate::{tracker::tracker_server::TrackerServer, DeliveryTracker};
+
+pub async fn report(
+    mut reporter: HealthReporter,
+    tracker: Arc<DeliveryTracker>,
+    check_interval: Duration,
+) {
+    let mut interval = tokio::time::interval(check_interval);
+    let mut serving = None;
+
+    loop {
+        interval.tick().await;
+
+        let available = tracker.is_store_available();
+        if serving == Some(available) {
+            continue;
+        }
+
+        if available {
+            info!("Subscription store is available, reporting SERVING");
+            reporter
+                .set_serving::<TrackerServer<DeliveryTracker>>()
+                .await;
+        } else {
+            warn!("Subscription store is unavailable, reporting NOT_SERVING");
+            reporter
+                .set_not_serving::<TrackerServer<DeliveryTracker>>()
+                .await;
+        }
+        serving = Some(available);
+    }
+}
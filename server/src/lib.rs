@@ -1,18 +1,180 @@
-use couriers::{kr::*, us::*};
-use structs::Courier;
+use std::{sync::Arc, time::Instant};
+
+use cache::SearchCache;
+use callback::CallbackDispatcher;
+use config::{CallbacksConfig, TrackerConfig};
+use couriers::{CourierInfo, TrackingError, TrackingResult};
+use email::EmailNotifier;
+use futures::future::join_all;
+use stats::CourierStats;
+use store::{Subscription, SubscriptionStore};
 use tonic::{Response, Status};
+use tracing::{info, info_span, warn, Instrument};
 
-use crate::tracker::tracker_server::Tracker;
+use crate::tracker::{tracker_server::Tracker, StatusKind};
 
-mod couriers;
-mod structs;
+pub mod auth;
+mod cache;
+mod callback;
+pub mod config;
+mod convert;
+pub mod email;
+pub mod health;
+pub mod logging;
+pub mod metrics;
+pub mod poller;
+mod stats;
+pub mod store;
 
 pub mod tracker {
     tonic::include_proto!("tracker");
+
+    pub const FILE_DESCRIPTOR_SET: &[u8] =
+        tonic::include_file_descriptor_set!("tracker_descriptor");
+}
+
+pub struct DeliveryTracker {
+    store: Arc<SubscriptionStore>,
+    stats: CourierStats,
+    config: TrackerConfig,
+    cache: SearchCache,
+    callbacks: CallbackDispatcher,
+    email: EmailNotifier,
 }
 
-#[derive(Default)]
-pub struct DeliveryTracker {}
+impl DeliveryTracker {
+    pub fn new(
+        store: SubscriptionStore,
+        config: TrackerConfig,
+        callbacks_config: CallbacksConfig,
+        email: EmailNotifier,
+    ) -> Self {
+        metrics::ACTIVE_SUBSCRIPTIONS.set(store.totals().1 as i64);
+        let store = Arc::new(store);
+        Self {
+            callbacks: CallbackDispatcher::new(callbacks_config, store.clone()),
+            store,
+            stats: CourierStats::default(),
+            cache: SearchCache::new(config.cache_ttl()),
+            config,
+            email,
+        }
+    }
+
+    fn check_email(&self, email: &str) -> Result<(), Status> {
+        if !self.email.is_enabled() {
+            return Err(Status::failed_precondition(
+                "Email notifications are not configured",
+            ));
+        }
+        email
+            .parse::<lettre::Address>()
+            .map(|_| ())
+            .map_err(|_| Status::invalid_argument("Invalid email address"))
+    }
+
+    /// Waits for callback deliveries in flight, see [`CallbackDispatcher::drain`].
+    pub async fn drain_callbacks(&self) {
+        self.callbacks.drain().await
+    }
+
+    pub fn flush_store(&self) -> std::io::Result<()> {
+        self.store.flush()
+    }
+
+    pub fn is_store_available(&self) -> bool {
+        self.store.check().is_ok()
+    }
+
+    fn support_couriers(&self, couriers: Vec<CourierInfo>) -> Vec<tracker::SupportCouriersDetail> {
+        couriers
+            .into_iter()
+            .filter(|courier| self.config.is_enabled(courier.id))
+            .map(Into::into)
+            .collect()
+    }
+
+    async fn track(&self, courier_id: &str, tracking_number: &str) -> Option<TrackingResult> {
+        if !self.config.is_enabled(courier_id) {
+            return None;
+        }
+        if let Some(info) = self.cache.get(courier_id, tracking_number) {
+            return Some(Ok(info));
+        }
+
+        let started = Instant::now();
+        let timeout = self.config.timeout(courier_id);
+        let result = tokio::time::timeout(timeout, couriers::track(courier_id, tracking_number))
+            .await
+            .unwrap_or_else(|_| {
+                Some(Err(TrackingError::RequestFailed(format!(
+                    "Timed out after {}s",
+                    timeout.as_secs()
+                ))))
+            });
+        if let Some(Ok(info)) = &result {
+            self.cache.insert(courier_id, tracking_number, info.clone());
+        }
+        if let Some(result) = &result {
+            let duration = started.elapsed();
+            let tracking_number = logging::mask_tracking_number(tracking_number);
+            metrics::COURIER_REQUESTS
+                .with_label_values(&[courier_id])
+                .inc();
+            metrics::COURIER_LATENCY
+                .with_label_values(&[courier_id])
+                .observe(duration.as_secs_f64());
+            match result {
+                Ok(_) => info!(
+                    courier_id,
+                    tracking_number = %tracking_number,
+                    duration_ms = duration.as_millis() as u64,
+                    outcome = "ok",
+                    "Scraped courier"
+                ),
+                Err(err) => {
+                    metrics::COURIER_ERRORS
+                        .with_label_values(&[courier_id, err.kind()])
+                        .inc();
+                    warn!(
+                        courier_id,
+                        tracking_number = %tracking_number,
+                        duration_ms = duration.as_millis() as u64,
+                        outcome = err.kind(),
+                        error = ?err,
+                        "Scraped courier"
+                    );
+                }
+            }
+            self.stats.record(courier_id, result);
+        }
+        result
+    }
+}
+
+fn into_search_response(result: TrackingResult) -> tracker::SearchResponse {
+    match result {
+        Ok(info) => tracker::SearchResponse {
+            status: StatusKind::Ok as i32,
+            message: None,
+            tracking_info: Some(info.into()),
+        },
+        Err(err) => {
+            use TrackingError::*;
+            let (status, message) = match err {
+                RequestFailed(err) => (StatusKind::RequestFailed, err),
+                WrongTrackingNumber(err) => (StatusKind::WrongTrackingNumber, err),
+                NotExistsTrackingNumber => (StatusKind::NotExistsTrackingNumber, String::new()),
+                ParsingError(err) => (StatusKind::RequestFailed, err),
+            };
+            tracker::SearchResponse {
+                status: status as i32,
+                message: Some(message),
+                tracking_info: None,
+            }
+        }
+    }
+}
 
 #[tonic::async_trait]
 impl Tracker for DeliveryTracker {
@@ -20,174 +182,331 @@ impl Tracker for DeliveryTracker {
         &self,
         request: tonic::Request<tracker::SearchRequest>,
     ) -> Result<tonic::Response<tracker::SearchResponse>, tonic::Status> {
+        let request_id = logging::request_id(&request);
         let tracking_request = request.into_inner();
-        let courier_id = tracking_request.courier_id.as_str();
-        let tracking_number = tracking_request.tracking_number.as_str();
-        let result = match courier_id {
-            // "cn.cainiao" => Cainiao::track(tracking_number).await,
-            "kr.chunil" => Chunil::track(tracking_number).await,
-            "kr.cjlogistics" => Cjlogistics::track(tracking_number).await,
-            "kr.cupost" => Cupost::track(tracking_number).await,
-            "kr.daesin" => Daesin::track(tracking_number).await,
-            "kr.epost" => Epost::track(tracking_number).await,
-            "kr.epostems" => EpostEMS::track(tracking_number).await,
-            "kr.gspostbox" => Gspostbox::track(tracking_number).await,
-            "kr.hanjin" => Hanjin::track(tracking_number).await,
-            "kr.ilyanglogis" => IlyangLogis::track(tracking_number).await,
-            "kr.kyoungdong" => Kyoungdong::track(tracking_number).await,
-            "kr.logen" => Logen::track(tracking_number).await,
-            "kr.lotte" => Lotte::track(tracking_number).await,
-            "us.fedex" => Fedex::track(tracking_number).await,
-            "us.warpex" => Warpex::track(tracking_number).await,
-            _ => {
-                return Err(Status::invalid_argument("Not supported courier"));
-            }
-        };
-        match result {
-            Ok(info) => Ok(Response::new(tracker::SearchResponse {
-                status: 0,
-                message: None,
-                tracking_info: Some(info),
-            })),
-            Err(err) => {
-                use structs::TrackingError::*;
-                let (status, message) = match err {
-                    RequestFailed(err) => (1, err),
-                    WrongTrackingNumber(err) => (2, err),
-                    NotExistsTrackingNumber => (3, String::new()),
-                    ParsingError(err) => (4, err),
-                };
-                Ok(Response::new(tracker::SearchResponse {
-                    status,
-                    message: Some(message.to_string()),
-                    tracking_info: None,
-                }))
-            }
-        }
+        let span = info_span!(
+            "search",
+            request_id = %request_id,
+            courier_id = %tracking_request.courier_id,
+            tracking_number = %logging::mask_tracking_number(&tracking_request.tracking_number),
+        );
+        let result = self
+            .track(
+                &tracking_request.courier_id,
+                &tracking_request.tracking_number,
+            )
+            .instrument(span)
+            .await
+            .ok_or_else(|| Status::invalid_argument("Not supported courier"))?;
+        Ok(Response::new(into_search_response(result)))
     }
 
     async fn get_support_couriers(
         &self,
         request: tonic::Request<tracker::SupportCouriersRequest>,
     ) -> Result<tonic::Response<tracker::SupportCouriersResponse>, tonic::Status> {
-        let mut couriers: Vec<tracker::SupportCouriersDetail> = Vec::new();
-        let tracking_number = request.into_inner().tracking_number;
-
-        // if Cainiao::validate(&tracking_number) {
-        //     couriers.push(tracker::SupportCouriersDetail {
-        //         id: Cainiao::id().to_string(),
-        //         name: Cainiao::name().to_string(),
-        //     });
-        // }
-
-        if Cjlogistics::validate(&tracking_number) {
-            couriers.push(tracker::SupportCouriersDetail {
-                id: Cjlogistics::id().to_string(),
-                name: Cjlogistics::name().to_string(),
-            });
-        }
-        if Epost::validate(&tracking_number) {
-            couriers.push(tracker::SupportCouriersDetail {
-                id: Epost::id().to_string(),
-                name: Epost::name().to_string(),
-            });
-        }
-        if EpostEMS::validate(&tracking_number) {
-            couriers.push(tracker::SupportCouriersDetail {
-                id: EpostEMS::id().to_string(),
-                name: EpostEMS::name().to_string(),
-            });
-        }
-        if Hanjin::validate(&tracking_number) {
-            couriers.push(tracker::SupportCouriersDetail {
-                id: Hanjin::id().to_string(),
-                name: Hanjin::name().to_string(),
-            });
-        }
-        if Logen::validate(&tracking_number) {
-            couriers.push(tracker::SupportCouriersDetail {
-                id: Logen::id().to_string(),
-                name: Logen::name().to_string(),
-            });
-        }
-        if Lotte::validate(&tracking_number) {
-            couriers.push(tracker::SupportCouriersDetail {
-                id: Lotte::id().to_string(),
-                name: Lotte::name().to_string(),
-            });
-        }
-        if Cupost::validate(&tracking_number) {
-            couriers.push(tracker::SupportCouriersDetail {
-                id: Cupost::id().to_string(),
-                name: Cupost::name().to_string(),
-            });
-        }
-        if Gspostbox::validate(&tracking_number) {
-            couriers.push(tracker::SupportCouriersDetail {
-                id: Gspostbox::id().to_string(),
-                name: Gspostbox::name().to_string(),
-            });
-        }
+        let couriers =
+            self.support_couriers(couriers::detect(&request.into_inner().tracking_number));
+        Ok(Response::new(tracker::SupportCouriersResponse { couriers }))
+    }
 
-        if Chunil::validate(&tracking_number) {
-            couriers.push(tracker::SupportCouriersDetail {
-                id: Chunil::id().to_string(),
-                name: Chunil::name().to_string(),
-            });
-        }
-        if Daesin::validate(&tracking_number) {
-            couriers.push(tracker::SupportCouriersDetail {
-                id: Daesin::id().to_string(),
-                name: Daesin::name().to_string(),
-            });
+    async fn extract_tracking_numbers(
+        &self,
+        request: tonic::Request<tracker::ExtractTrackingNumbersRequest>,
+    ) -> Result<tonic::Response<tracker::ExtractTrackingNumbersResponse>, tonic::Status> {
+        let candidates = couriers::extract(&request.into_inner().text)
+            .into_iter()
+            .filter_map(|detected| {
+                let couriers = self.support_couriers(detected.couriers);
+                if couriers.is_empty() {
+                    None
+                } else {
+                    Some(tracker::ExtractedTrackingNumber {
+                        tracking_number: detected.tracking_number,
+                        couriers,
+                    })
+                }
+            })
+            .collect();
+        Ok(Response::new(tracker::ExtractTrackingNumbersResponse {
+            candidates,
+        }))
+    }
+
+    async fn add_track(
+        &self,
+        request: tonic::Request<tracker::AddTrackRequest>,
+    ) -> Result<tonic::Response<tracker::AddTrackResponse>, tonic::Status> {
+        auth::authorize(&request, &request.get_ref().user_id)?;
+        let request_id = logging::request_id(&request);
+        let request = request.into_inner();
+        let callback_url = request.callback_url.filter(|url| !url.is_empty());
+        if let Some(url) = &callback_url {
+            if !self.callbacks.is_enabled() {
+                return Err(Status::failed_precondition("Callbacks are not configured"));
+            }
+            self.callbacks
+                .check_url(url)
+                .map_err(Status::invalid_argument)?;
         }
-        if IlyangLogis::validate(&tracking_number) {
-            couriers.push(tracker::SupportCouriersDetail {
-                id: IlyangLogis::id().to_string(),
-                name: IlyangLogis::name().to_string(),
-            });
+        let email = request.email.filter(|email| !email.is_empty());
+        if let Some(email) = &email {
+            self.check_email(email)?;
         }
-        if Kyoungdong::validate(&tracking_number) {
-            couriers.push(tracker::SupportCouriersDetail {
-                id: Kyoungdong::id().to_string(),
-                name: Kyoungdong::name().to_string(),
-            });
+        if self
+            .store
+            .get(
+                &request.user_id,
+                &request.courier_id,
+                &request.tracking_number,
+            )
+            .is_some()
+        {
+            return Ok(Response::new(tracker::AddTrackResponse {
+                status: StatusKind::TrackingAlreadyExists as i32,
+                response: None,
+            }));
         }
 
-        if Fedex::validate(&tracking_number) {
-            couriers.push(tracker::SupportCouriersDetail {
-                id: Fedex::id().to_string(),
-                name: Fedex::name().to_string(),
-            });
-        }
-        if Warpex::validate(&tracking_number) {
-            couriers.push(tracker::SupportCouriersDetail {
-                id: Warpex::id().to_string(),
-                name: Warpex::name().to_string(),
-            });
+        let span = info_span!(
+            "add_track",
+            request_id = %request_id,
+            courier_id = %request.courier_id,
+            tracking_number = %logging::mask_tracking_number(&request.tracking_number),
+        );
+        let result = self
+            .track(&request.courier_id, &request.tracking_number)
+            .instrument(span)
+            .await
+            .ok_or_else(|| Status::invalid_argument("Not supported courier"))?;
+        let response = into_search_response(result);
+        if response.status() != StatusKind::Ok {
+            return Ok(Response::new(tracker::AddTrackResponse {
+                status: response.status,
+                response: Some(response),
+            }));
         }
 
-        Ok(Response::new(tracker::SupportCouriersResponse { couriers }))
-    }
+        let added = self.store.add(
+            &request.user_id,
+            Subscription {
+                courier_id: request.courier_id,
+                tracking_number: request.tracking_number,
+                added_by: request.added_by,
+                label: request.label.filter(|label| !label.is_empty()),
+                note: request.note.filter(|note| !note.is_empty()),
+                tags: request.tags,
+                callback_url,
+                email,
+                seen_events: response
+                    .tracking_info
+                    .as_ref()
+                    .map(|info| info.tracks.len())
+                    .unwrap_or_default(),
+                delivered: response
+                    .tracking_info
+                    .as_ref()
+                    .map(|info| info.is_delivered)
+                    .unwrap_or_default(),
+                pending_callbacks: Vec::new(),
+                callback_attempts: Vec::new(),
+            },
+        );
+        if !added {
+            return Ok(Response::new(tracker::AddTrackResponse {
+                status: StatusKind::TrackingAlreadyExists as i32,
+                response: None,
+            }));
+        }
+        self.store
+            .flush()
+            .map_err(|err| Status::internal(err.to_string()))?;
+        metrics::ACTIVE_SUBSCRIPTIONS.inc();
 
-    async fn add_track(
-        &self,
-        request: tonic::Request<tracker::AddTrackRequest>,
-    ) -> Result<tonic::Response<tracker::AddTrackResponse>, tonic::Status> {
-        todo!()
+        Ok(Response::new(tracker::AddTrackResponse {
+            status: StatusKind::Ok as i32,
+            response: Some(response),
+        }))
     }
 
     async fn remove_track(
         &self,
         request: tonic::Request<tracker::RemoveTrackRequest>,
     ) -> Result<tonic::Response<tracker::RemoveTrackResponse>, tonic::Status> {
-        todo!()
+        auth::authorize(&request, &request.get_ref().user_id)?;
+        // Only front-ends that check chat permissions themselves may skip the owner check.
+        let is_chat_admin = request.get_ref().is_chat_admin && auth::trusts_chat_admin(&request);
+        let request = request.into_inner();
+        let status = match self.store.get(
+            &request.user_id,
+            &request.courier_id,
+            &request.tracking_number,
+        ) {
+            None => StatusKind::TrackingNotExists,
+            Some(subscription)
+                if subscription.added_by != request.requested_by && !is_chat_admin =>
+            {
+                StatusKind::PermissionDenied
+            }
+            Some(_) => {
+                if self.store.remove(
+                    &request.user_id,
+                    &request.courier_id,
+                    &request.tracking_number,
+                ) {
+                    self.store
+                        .flush()
+                        .map_err(|err| Status::internal(err.to_string()))?;
+                    metrics::ACTIVE_SUBSCRIPTIONS.dec();
+                    StatusKind::Ok
+                } else {
+                    StatusKind::TrackingNotExists
+                }
+            }
+        };
+
+        Ok(Response::new(tracker::RemoveTrackResponse {
+            status: status as i32,
+        }))
+    }
+
+    async fn update_track(
+        &self,
+        request: tonic::Request<tracker::UpdateTrackRequest>,
+    ) -> Result<tonic::Response<tracker::UpdateTrackResponse>, tonic::Status> {
+        auth::authorize(&request, &request.get_ref().user_id)?;
+        let request = request.into_inner();
+        if request.courier_id.is_empty() {
+            return Err(Status::invalid_argument("courier_id is required"));
+        }
+        if let Some(email) = request.email.as_deref().filter(|email| !email.is_empty()) {
+            self.check_email(email)?;
+        }
+        let updated = self.store.update(
+            &request.user_id,
+            &request.courier_id,
+            &request.tracking_number,
+            |subscription| {
+                if let Some(label) = request.label {
+                    subscription.label = Some(label).filter(|label| !label.is_empty());
+                }
+                if let Some(note) = request.note {
+                    subscription.note = Some(note).filter(|note| !note.is_empty());
+                }
+                if let Some(tags) = request.tags {
+                    subscription.tags = tags.tags;
+                }
+                if let Some(email) = request.email {
+                    subscription.email = Some(email).filter(|email| !email.is_empty());
+                }
+            },
+        );
+
+        let status = if updated.is_some() {
+            self.store
+                .flush()
+                .map_err(|err| Status::internal(err.to_string()))?;
+            StatusKind::Ok
+        } else {
+            StatusKind::TrackingNotExists
+        };
+
+        Ok(Response::new(tracker::UpdateTrackResponse {
+            status: status as i32,
+        }))
     }
 
     async fn tracking_list(
         &self,
         request: tonic::Request<tracker::TrackingListRequest>,
     ) -> Result<tonic::Response<tracker::TrackingListResponse>, tonic::Status> {
-        todo!()
+        auth::authorize(&request, &request.get_ref().user_id)?;
+        let request_id = logging::request_id(&request);
+        let user_id = request.into_inner().user_id;
+        let span = info_span!("tracking_list", request_id = %request_id);
+        let entries = join_all(self.store.list(&user_id).into_iter().map(
+            |subscription| async move {
+                let result = self
+                    .track(&subscription.courier_id, &subscription.tracking_number)
+                    .await;
+                let tracking_info = match result {
+                    Some(Ok(info)) => info.into(),
+                    _ => tracker::TrackingInfo {
+                        id: subscription.courier_id,
+                        tracking_number: subscription.tracking_number,
+                        ..Default::default()
+                    },
+                };
+                tracker::TrackingListEntry {
+                    tracking_info: Some(tracking_info),
+                    label: subscription.label,
+                    note: subscription.note,
+                    tags: subscription.tags,
+                }
+            },
+        ))
+        .instrument(span)
+        .await;
+
+        Ok(Response::new(tracker::TrackingListResponse { entries }))
+    }
+
+    async fn list_callback_deliveries(
+        &self,
+        request: tonic::Request<tracker::CallbackDeliveriesRequest>,
+    ) -> Result<tonic::Response<tracker::CallbackDeliveriesResponse>, tonic::Status> {
+        auth::authorize(&request, &request.get_ref().user_id)?;
+        let request = request.into_inner();
+        let deliveries = self
+            .callbacks
+            .attempts(
+                &request.user_id,
+                &request.courier_id,
+                &request.tracking_number,
+            )
+            .into_iter()
+            .map(|attempt| tracker::CallbackDelivery {
+                url: attempt.url,
+                attempt: attempt.attempt,
+                attempted_at: attempt.attempted_at,
+                success: attempt.error.is_none(),
+                status_code: attempt.status_code.map(u32::from),
+                error: attempt.error,
+            })
+            .collect();
+        Ok(Response::new(tracker::CallbackDeliveriesResponse {
+            deliveries,
+        }))
+    }
+
+    async fn get_subscription_stats(
+        &self,
+        request: tonic::Request<tracker::SubscriptionStatsRequest>,
+    ) -> Result<tonic::Response<tracker::SubscriptionStatsResponse>, tonic::Status> {
+        auth::authorize_admin(&request)?;
+        let (users, subscriptions) = self.store.totals();
+        Ok(Response::new(tracker::SubscriptionStatsResponse {
+            users: users as u64,
+            subscriptions: subscriptions as u64,
+        }))
+    }
+
+    async fn get_courier_health(
+        &self,
+        request: tonic::Request<tracker::CourierHealthRequest>,
+    ) -> Result<tonic::Response<tracker::CourierHealthResponse>, tonic::Status> {
+        auth::authorize_admin(&request)?;
+        let couriers = self
+            .stats
+            .snapshot()
+            .into_iter()
+            .map(|(id, counter)| tracker::CourierHealth {
+                id,
+                success: counter.success,
+                failure: counter.failure,
+                last_error: counter.last_error,
+            })
+            .collect();
+        Ok(Response::new(tracker::CourierHealthResponse { couriers }))
     }
 }
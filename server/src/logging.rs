@@ -0,0 +1,31 @@
+use once_cell::sync::OnceCell;
+
+pub const REQUEST_ID_HEADER: &str = "x-request-id";
+
+static MASK_TRACKING_NUMBERS: OnceCell<bool> = OnceCell::new();
+
+pub fn set_mask_tracking_numbers(mask: bool) {
+    MASK_TRACKING_NUMBERS.set(mask).ok();
+}
+
+pub fn request_id<T>(request: &tonic::Request<T>) -> String {
+    request
+        .metadata()
+        .get(REQUEST_ID_HEADER)
+        .and_then(|value| value.to_str().ok())
+        .unwrap_or("-")
+        .to_string()
+}
+
+pub fn mask_tracking_number(tracking_number: &str) -> String {
+    if !MASK_TRACKING_NUMBERS.get().copied().unwrap_or(false) {
+        return tracking_number.to_string();
+    }
+
+    let visible = tracking_number.chars().count().saturating_sub(4);
+    tracking_number
+        .chars()
+        .enumerate()
+        .map(|(index, c)| if index < visible { '*' } else { c })
+        .collect()
+}
@@ -1,30 +1,229 @@
 #![warn(clippy::all)]
 
-use std::net::SocketAddr;
+use std::{sync::Arc, time::Duration};
 
 use dotenv::dotenv;
-use log::info;
-use server::{tracker::tracker_server::TrackerServer, DeliveryTracker};
-use tokio::runtime::Runtime;
-use tonic::transport::Server;
+use server::{
+    auth::{ApiKeyInterceptor, ApiKeys},
+    config::{Config, LogFormat, TlsConfig},
+    email::EmailNotifier,
+    health, logging, metrics, poller,
+    store::SubscriptionStore,
+    tracker::{self, tracker_server::TrackerServer},
+    DeliveryTracker,
+};
+use tokio::{
+    runtime::Runtime,
+    sync::{watch, Notify},
+    time::Instant,
+};
+use tonic::{
+    codegen::InterceptedService,
+    transport::{Certificate, Identity, Server, ServerTlsConfig},
+};
+use tracing::{error, info, warn};
+
+fn init_tracing(format: LogFormat) {
+    let filter = tracing_subscriber::EnvFilter::try_from_default_env()
+        .unwrap_or_else(|_| tracing_subscriber::EnvFilter::new("info"));
+    let subscriber = tracing_subscriber::fmt().with_env_filter(filter);
+    match format {
+        LogFormat::Json => subscriber.json().init(),
+        LogFormat::Text => subscriber.init(),
+    }
+}
+
+const SHUTDOWN_TIMEOUT: Duration = Duration::from_secs(25);
+
+async fn shutdown_signal() {
+    #[cfg(unix)]
+    {
+        use tokio::signal::unix::{signal, SignalKind};
+
+        let mut terminate = signal(SignalKind::terminate()).expect("Failed to listen for SIGTERM");
+        tokio::select! {
+            _ = tokio::signal::ctrl_c() => {}
+            _ = terminate.recv() => {}
+        }
+    }
+    #[cfg(not(unix))]
+    tokio::signal::ctrl_c()
+        .await
+        .expect("Failed to listen for ctrl-c");
+}
+
+fn tls_config(config: &TlsConfig) -> std::io::Result<Option<ServerTlsConfig>> {
+    let (cert_path, key_path) = match (&config.cert_path, &config.key_path) {
+        (Some(cert_path), Some(key_path)) => (cert_path, key_path),
+        _ => return Ok(None),
+    };
+
+    let cert = std::fs::read(cert_path)?;
+    let key = std::fs::read(key_path)?;
+    let mut tls_config = ServerTlsConfig::new().identity(Identity::from_pem(cert, key));
+
+    if let Some(ca_path) = &config.client_ca_path {
+        let ca = std::fs::read(ca_path)?;
+        tls_config = tls_config.client_ca_root(Certificate::from_pem(ca));
+    }
+
+    Ok(Some(tls_config))
+}
+
+fn exit_with_error(context: &str, err: impl std::fmt::Display) -> ! {
+    eprintln!("{}: {}", context, err);
+    std::process::exit(1)
+}
 
 fn main() {
     dotenv().ok();
-    pretty_env_logger::init();
 
-    let bind_address = std::env::var("BIND_ADDR")
-        .ok()
-        .and_then(|addr| addr.parse().ok())
-        .expect("cannot find bind address from BIND_ADDR");
+    let config = Config::load().unwrap_or_else(|err| exit_with_error("Invalid configuration", err));
+    init_tracing(config.log.format);
+    logging::set_mask_tracking_numbers(config.log.mask_tracking_numbers);
 
-    Runtime::new().unwrap().block_on(run(bind_address));
+    let store = match &config.store_path {
+        Some(path) => SubscriptionStore::open(path)
+            .unwrap_or_else(|err| exit_with_error("Can't open subscription store", err)),
+        None => SubscriptionStore::default(),
+    };
+    if let Some(namespace) = &config.legacy_user_namespace {
+        let migrated = store.migrate_namespace(namespace);
+        if migrated > 0 {
+            info!(migrated, namespace = %namespace, "Moved legacy user ids into namespace");
+            store
+                .flush()
+                .unwrap_or_else(|err| exit_with_error("Can't write subscription store", err));
+        }
+    }
+
+    let api_keys = match &config.api_keys_path {
+        Some(path) => Some(
+            ApiKeys::open(path).unwrap_or_else(|err| exit_with_error("Can't load API keys", err)),
+        ),
+        None => {
+            warn!("api_keys_path is not set, the tracker API accepts any caller");
+            None
+        }
+    };
+
+    let tls_config = tls_config(&config.tls)
+        .unwrap_or_else(|err| exit_with_error("Can't read TLS certificates", err));
+
+    let email = EmailNotifier::new(&config.email)
+        .unwrap_or_else(|err| exit_with_error("Invalid email configuration", err));
+
+    Runtime::new()
+        .unwrap()
+        .block_on(run(config, store, api_keys, tls_config, email));
 }
 
-async fn run(address: SocketAddr) {
+async fn run(
+    config: Config,
+    store: SubscriptionStore,
+    api_keys: Option<ApiKeys>,
+    tls_config: Option<ServerTlsConfig>,
+    email: EmailNotifier,
+) {
+    if let Some(metrics_address) = config.metrics_addr {
+        info!("Serving metrics on {}", metrics_address);
+        tokio::spawn(async move {
+            if let Err(err) = metrics::serve(metrics_address).await {
+                error!("Metrics server stopped: {}", err);
+            }
+        });
+    }
+
+    let poll_interval = config.callbacks.poll_interval();
+    let tracker = Arc::new(DeliveryTracker::new(
+        store,
+        config.tracker,
+        config.callbacks,
+        email,
+    ));
+
+    let (mut health_reporter, health_service) = tonic_health::server::health_reporter();
+    health_reporter
+        .set_service_status("", tonic_health::ServingStatus::Serving)
+        .await;
+    let health_task = tokio::spawn(health::report(
+        health_reporter.clone(),
+        tracker.clone(),
+        Duration::from_secs(config.health_check_interval_secs),
+    ));
+    let (stop_poller, poller_stopped) = watch::channel(false);
+    let poller_task = tokio::spawn(poller::run(tracker.clone(), poll_interval, poller_stopped));
+
+    let reflection_service = tonic_reflection::server::Builder::configure()
+        .register_encoded_file_descriptor_set(tracker::FILE_DESCRIPTOR_SET)
+        .build()
+        .expect("Can't build reflection service");
+
+    let mut builder = Server::builder();
+    if let Some(tls_config) = tls_config {
+        info!("Serving gRPC over TLS");
+        builder = builder
+            .tls_config(tls_config)
+            .expect("Invalid TLS configuration");
+    }
+
+    let shutdown_started = Arc::new(Notify::new());
+    let shutdown = {
+        let shutdown_started = shutdown_started.clone();
+        async move {
+            shutdown_signal().await;
+            info!("Shutdown requested, draining in-flight requests...");
+            health_task.abort();
+            let _ = stop_poller.send(true);
+            health_reporter
+                .set_service_status("", tonic_health::ServingStatus::NotServing)
+                .await;
+            health_reporter
+                .set_not_serving::<TrackerServer<DeliveryTracker>>()
+                .await;
+            shutdown_started.notify_one();
+        }
+    };
+
     info!("Try running server...");
-    Server::builder()
-        .add_service(TrackerServer::new(DeliveryTracker::default()))
-        .serve(address)
+    let server = builder
+        .add_service(health_service)
+        .add_service(reflection_service)
+        .add_service(InterceptedService::new(
+            TrackerServer::from_arc(tracker.clone()),
+            ApiKeyInterceptor::new(api_keys),
+        ))
+        .serve_with_shutdown(config.bind_addr, shutdown);
+    tokio::pin!(server);
+
+    tokio::select! {
+        result = &mut server => result.expect("Can't run server"),
+        _ = async {
+            shutdown_started.notified().await;
+            tokio::time::sleep(SHUTDOWN_TIMEOUT).await;
+        } => warn!("In-flight requests did not finish within {:?}", SHUTDOWN_TIMEOUT),
+    }
+
+    let deadline = Instant::now() + SHUTDOWN_TIMEOUT;
+    if tokio::time::timeout_at(deadline, poller_task)
+        .await
+        .is_err()
+    {
+        warn!("Poller did not stop within {:?}", SHUTDOWN_TIMEOUT);
+    }
+    if tokio::time::timeout_at(deadline, tracker.drain_callbacks())
         .await
-        .expect("Can't run server");
+        .is_err()
+    {
+        warn!(
+            "Callback deliveries did not finish within {:?}",
+            SHUTDOWN_TIMEOUT
+        );
+    }
+
+    match tracker.flush_store() {
+        Ok(()) => info!("Subscription store flushed"),
+        Err(err) => error!("Failed to flush subscription store: {}", err),
+    }
+    info!("Server stopped");
 }
@@ -0,0 +1,92 @@
+use std::{convert::Infallible, net::SocketAddr};
+
+use hyper::{
+    header::{HeaderValue, CONTENT_TYPE},
+    service::{make_service_fn, service_fn},
+    Body, Method, Request, Response, Server, StatusCode,
+};
+use once_cell::sync::Lazy;
+use prometheus::{
+    register_histogram_vec, register_int_counter_vec, register_int_gauge, Encoder, HistogramVec,
+    IntCounterVec, IntGauge, TextEncoder, TEXT_FORMAT,
+};
+
+pub static COURIER_REQUESTS: Lazy<IntCounterVec> = Lazy::new(|| {
+    register_int_counter_vec!(
+        "tracker_courier_requests_total",
+        "Number of tracking requests sent to each courier",
+        &["courier"]
+    )
+    .unwrap()
+});
+
+pub static COURIER_LATENCY: Lazy<HistogramVec> = Lazy::new(|| {
+    register_histogram_vec!(
+        "tracker_courier_request_duration_seconds",
+        "Time taken to fetch tracking information from each courier",
+        &["courier"]
+    )
+    .unwrap()
+});
+
+pub static COURIER_ERRORS: Lazy<IntCounterVec> = Lazy::new(|| {
+    register_int_counter_vec!(
+        "tracker_courier_errors_total",
+        "Number of failed tracking requests by courier and error kind",
+        &["courier", "kind"]
+    )
+    .unwrap()
+});
+
+pub static ACTIVE_SUBSCRIPTIONS: Lazy<IntGauge> = Lazy::new(|| {
+    register_int_gauge!(
+        "tracker_active_subscriptions",
+        "Number of tracked parcels across all users"
+    )
+    .unwrap()
+});
+
+pub static CALLBACK_DELIVERIES: Lazy<IntCounterVec> = Lazy::new(|| {
+    register_int_counter_vec!(
+        "tracker_callback_deliveries_total",
+        "Number of callback delivery attempts by result",
+        &["result"]
+    )
+    .unwrap()
+});
+
+pub static EMAIL_NOTIFICATIONS: Lazy<IntCounterVec> = Lazy::new(|| {
+    register_int_counter_vec!(
+        "tracker_email_notifications_total",
+        "Number of email notifications by result",
+        &["result"]
+    )
+    .unwrap()
+});
+
+pub fn gather() -> String {
+    let mut buffer = Vec::new();
+    TextEncoder::new()
+        .encode(&prometheus::gather(), &mut buffer)
+        .expect("Failed to encode metrics");
+    String::from_utf8(buffer).expect("Metrics are not valid UTF-8")
+}
+
+async fn handle(request: Request<Body>) -> Result<Response<Body>, Infallible> {
+    let mut response = Response::new(Body::empty());
+    match (request.method(), request.uri().path()) {
+        (&Method::GET, "/metrics") => {
+            response
+                .headers_mut()
+                .insert(CONTENT_TYPE, HeaderValue::from_static(TEXT_FORMAT));
+            *response.body_mut() = Body::from(gather());
+        }
+        _ => *response.status_mut() = StatusCode::NOT_FOUND,
+    }
+    Ok(response)
+}
+
+pub async fn serve(address: SocketAddr) -> hyper::Result<()> {
+    let make_service = make_service_fn(|_| async { Ok::<_, Infallible>(service_fn(handle)) });
+    Server::try_bind(&address)?.serve(make_service).await
+}
@@ -0,0 +1,110 @@
+use std::{sync::Arc, time::Duration};
+
+use tokio::sync::watch;
+use tracing::{error, info};
+
+use crate::{
+    callback::{self, CallbackPayload},
+    email::EmailContext,
+    DeliveryTracker,
+};
+
+/// Periodically re-tracks subscriptions with a callback or email and reports new events.
+///
+/// Callback payloads go through the subscription's outbox, so they are retried
+/// on later polls until the callback url accepts them.
+///
+/// Returns once `stop` is set, never in the middle of a poll, so the store is
+/// consistent when it is flushed on shutdown.
+pub async fn run(
+    tracker: Arc<DeliveryTracker>,
+    poll_interval: Duration,
+    mut stop: watch::Receiver<bool>,
+) {
+    let mut interval = tokio::time::interval(poll_interval);
+    interval.tick().await;
+
+    while !*stop.borrow() {
+        tokio::select! {
+            _ = interval.tick() => poll(&tracker).await,
+            result = stop.changed() => {
+                if result.is_err() {
+                    break;
+                }
+            }
+        }
+    }
+}
+
+async fn poll(tracker: &DeliveryTracker) {
+    let subscriptions: Vec<_> = tracker
+        .store
+        .all()
+        .into_iter()
+        .filter(|(_, subscription)| {
+            (subscription.callback_url.is_some() || subscription.email.is_some())
+                && !subscription.delivered
+        })
+        .collect();
+    if subscriptions.is_empty() {
+        tracker.callbacks.dispatch_pending();
+        return;
+    }
+
+    let mut changed = 0;
+    for (user_id, subscription) in subscriptions {
+        let info = match tracker
+            .track(&subscription.courier_id, &subscription.tracking_number)
+            .await
+        {
+            Some(Ok(info)) => info,
+            _ => continue,
+        };
+        if info.tracks.len() <= subscription.seen_events
+            && info.is_delivered == subscription.delivered
+        {
+            continue;
+        }
+
+        let events: Vec<_> = info
+            .tracks
+            .iter()
+            .skip(subscription.seen_events)
+            .cloned()
+            .collect();
+        // Events are only marked as seen once the email went out, so a failed send is
+        // retried with the same events on the next poll.
+        if let Some(email) = &subscription.email {
+            let context = EmailContext::new(subscription.label.as_deref(), &info, &events);
+            if !tracker.email.send(email, &context).await {
+                continue;
+            }
+        }
+
+        let payload = subscription
+            .callback_url
+            .as_ref()
+            .map(|_| CallbackPayload::new(&user_id, &info, events.clone()));
+        tracker.store.update(
+            &user_id,
+            &subscription.courier_id,
+            &subscription.tracking_number,
+            |subscription| {
+                subscription.seen_events = info.tracks.len();
+                subscription.delivered = info.is_delivered;
+                if let Some(payload) = payload {
+                    callback::enqueue(subscription, payload);
+                }
+            },
+        );
+        changed += 1;
+    }
+
+    if changed > 0 {
+        info!(changed, "Detected tracking updates");
+        if let Err(err) = tracker.store.flush() {
+            error!("Failed to flush subscription store: {}", err);
+        }
+    }
+    tracker.callbacks.dispatch_pending();
+}
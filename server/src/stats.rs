@@ -0,0 +1,43 @@
+use std::{collections::HashMap, sync::RwLock};
+
+use couriers::{TrackingError, TrackingResult};
+
+#[derive(Clone, Debug, Default)]
+pub struct CourierCounter {
+    pub success: u64,
+    pub failure: u64,
+    pub last_error: Option<String>,
+}
+
+#[derive(Default)]
+pub struct CourierStats {
+    counters: RwLock<HashMap<String, CourierCounter>>,
+}
+
+impl CourierStats {
+    pub fn record(&self, courier_id: &str, result: &TrackingResult) {
+        let mut map = match self.counters.write() {
+            Ok(map) => map,
+            Err(_) => return,
+        };
+        let counter = map.entry(courier_id.to_string()).or_default();
+        match result {
+            Ok(_) | Err(TrackingError::NotExistsTrackingNumber) => counter.success += 1,
+            Err(TrackingError::RequestFailed(err)) | Err(TrackingError::ParsingError(err)) => {
+                counter.failure += 1;
+                counter.last_error = Some(err.clone());
+            }
+            Err(TrackingError::WrongTrackingNumber(_)) => {}
+        }
+    }
+
+    pub fn snapshot(&self) -> Vec<(String, CourierCounter)> {
+        let mut counters = self
+            .counters
+            .read()
+            .map(|map| map.clone().into_iter().collect::<Vec<_>>())
+            .unwrap_or_default();
+        counters.sort_by(|(a, _), (b, _)| a.cmp(b));
+        counters
+    }
+}
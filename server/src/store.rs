@@ -0,0 +1,277 @@
+use std::{
+    collections::HashMap,
+    fs,
+    io::{self, Write},
+    path::{Path, PathBuf},
+    sync::RwLock,
+};
+
+use serde::{Deserialize, Serialize};
+
+use crate::callback::{CallbackAttempt, CallbackPayload};
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Subscription {
+    pub courier_id: String,
+    pub tracking_number: String,
+    pub added_by: String,
+    #[serde(default)]
+    pub label: Option<String>,
+    #[serde(default)]
+    pub note: Option<String>,
+    #[serde(default)]
+    pub tags: Vec<String>,
+    #[serde(default)]
+    pub callback_url: Option<String>,
+    #[serde(default)]
+    pub email: Option<String>,
+    /// Number of tracking events already reported by the poller.
+    #[serde(default)]
+    pub seen_events: usize,
+    #[serde(default)]
+    pub delivered: bool,
+    /// Callback payloads the callback url has not accepted yet, oldest first.
+    #[serde(default)]
+    pub pending_callbacks: Vec<CallbackPayload>,
+    /// Most recent callback delivery attempts, oldest first.
+    #[serde(default)]
+    pub callback_attempts: Vec<CallbackAttempt>,
+}
+
+#[derive(Default)]
+pub struct SubscriptionStore {
+    path: Option<PathBuf>,
+    subscriptions: RwLock<HashMap<String, Vec<Subscription>>>,
+}
+
+impl SubscriptionStore {
+    pub fn open<P: AsRef<Path>>(path: P) -> io::Result<Self> {
+        let path = path.as_ref().to_path_buf();
+        let subscriptions = if path.exists() {
+            serde_json::from_str(&fs::read_to_string(&path)?)
+                .map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))?
+        } else {
+            HashMap::new()
+        };
+
+        Ok(Self {
+            path: Some(path),
+            subscriptions: RwLock::new(subscriptions),
+        })
+    }
+
+    pub fn migrate_namespace(&self, namespace: &str) -> usize {
+        let mut map = match self.subscriptions.write() {
+            Ok(map) => map,
+            Err(_) => return 0,
+        };
+        let legacy = map
+            .keys()
+            .filter(|user_id| !user_id.contains(':'))
+            .cloned()
+            .collect::<Vec<String>>();
+        for user_id in &legacy {
+            if let Some(subscriptions) = map.remove(user_id) {
+                map.insert(format!("{}:{}", namespace, user_id), subscriptions);
+            }
+        }
+        legacy.len()
+    }
+
+    pub fn list(&self, user_id: &str) -> Vec<Subscription> {
+        self.subscriptions
+            .read()
+            .ok()
+            .and_then(|map| map.get(user_id).cloned())
+            .unwrap_or_default()
+    }
+
+    pub fn all(&self) -> Vec<(String, Subscription)> {
+        self.subscriptions
+            .read()
+            .map(|map| {
+                map.iter()
+                    .flat_map(|(user_id, subscriptions)| {
+                        subscriptions
+                            .iter()
+                            .map(move |subscription| (user_id.clone(), subscription.clone()))
+                    })
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    pub fn totals(&self) -> (usize, usize) {
+        self.subscriptions
+            .read()
+            .map(|map| (map.len(), map.values().map(Vec::len).sum()))
+            .unwrap_or_default()
+    }
+
+    pub fn get(
+        &self,
+        user_id: &str,
+        courier_id: &str,
+        tracking_number: &str,
+    ) -> Option<Subscription> {
+        self.list(user_id).into_iter().find(|subscription| {
+            subscription.courier_id == courier_id && subscription.tracking_number == tracking_number
+        })
+    }
+
+    pub fn add(&self, user_id: &str, subscription: Subscription) -> bool {
+        if let Ok(mut map) = self.subscriptions.write() {
+            let subscriptions = map.entry(user_id.to_string()).or_default();
+            if subscriptions.iter().any(|old| {
+                old.courier_id == subscription.courier_id
+                    && old.tracking_number == subscription.tracking_number
+            }) {
+                return false;
+            }
+            subscriptions.push(subscription);
+            true
+        } else {
+            false
+        }
+    }
+
+    pub fn update<F>(
+        &self,
+        user_id: &str,
+        courier_id: &str,
+        tracking_number: &str,
+        f: F,
+    ) -> Option<Subscription>
+    where
+        F: FnOnce(&mut Subscription),
+    {
+        let mut map = self.subscriptions.write().ok()?;
+        let subscription = map.get_mut(user_id)?.iter_mut().find(|subscription| {
+            subscription.courier_id == courier_id && subscription.tracking_number == tracking_number
+        })?;
+        f(subscription);
+        Some(subscription.clone())
+    }
+
+    pub fn remove(&self, user_id: &str, courier_id: &str, tracking_number: &str) -> bool {
+        if let Ok(mut map) = self.subscriptions.write() {
+            if let Some(subscriptions) = map.get_mut(user_id) {
+                let len = subscriptions.len();
+                subscriptions.retain(|subscription| {
+                    subscription.courier_id != courier_id
+                        || subscription.tracking_number != tracking_number
+                });
+                let removed = subscriptions.len() != len;
+                if subscriptions.is_empty() {
+                    map.remove(user_id);
+                }
+                return removed;
+            }
+        }
+        false
+    }
+
+    pub fn check(&self) -> io::Result<()> {
+        if self.subscriptions.read().is_err() {
+            return Err(io::Error::new(io::ErrorKind::Other, "store is poisoned"));
+        }
+        let path = match &self.path {
+            Some(path) => path,
+            None => return Ok(()),
+        };
+        let metadata = if path.exists() {
+            fs::metadata(path)?
+        } else {
+            let parent = path
+                .parent()
+                .filter(|parent| !parent.as_os_str().is_empty())
+                .unwrap_or_else(|| Path::new("."));
+            fs::metadata(parent)?
+        };
+        if metadata.permissions().readonly() {
+            return Err(io::Error::new(
+                io::ErrorKind::PermissionDenied,
+                "store is read-only",
+            ));
+        }
+        Ok(())
+    }
+
+    pub fn flush(&self) -> io::Result<()> {
+        let path = match &self.path {
+            Some(path) => path,
+            None => return Ok(()),
+        };
+        let json = match self.subscriptions.read() {
+            Ok(map) => serde_json::to_string(&*map)?,
+            Err(_) => return Err(io::Error::new(io::ErrorKind::Other, "store is poisoned")),
+        };
+        write_atomically(path, &json)
+    }
+}
+
+/// Replaces `path` through a synced `<path>.tmp`, so a crash mid-write leaves the old file intact.
+fn write_atomically(path: &Path, contents: &str) -> io::Result<()> {
+    let mut tmp_path = path.as_os_str().to_owned();
+    tmp_path.push(".tmp");
+    let tmp_path = PathBuf::from(tmp_path);
+
+    let mut file = fs::File::create(&tmp_path)?;
+    file.write_all(contents.as_bytes())?;
+    file.sync_all()?;
+    fs::rename(&tmp_path, path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn subscription(courier_id: &str) -> Subscription {
+        Subscription {
+            courier_id: courier_id.to_string(),
+            tracking_number: "1234".to_string(),
+            added_by: "user".to_string(),
+            label: None,
+            note: None,
+            tags: Vec::new(),
+            callback_url: None,
+            email: None,
+            seen_events: 0,
+            delivered: false,
+            pending_callbacks: Vec::new(),
+            callback_attempts: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn flushes_through_a_temporary_file() {
+        let path =
+            std::env::temp_dir().join(format!("tracker-store-test-{}.json", std::process::id()));
+        let store = SubscriptionStore::open(&path).unwrap();
+        assert!(store.add("user", subscription("kr.cjlogistics")));
+        store.flush().unwrap();
+
+        let reopened = SubscriptionStore::open(&path).unwrap();
+        let tmp_exists = path.with_extension("json.tmp").exists();
+        fs::remove_file(&path).ok();
+
+        assert!(reopened.get("user", "kr.cjlogistics", "1234").is_some());
+        assert!(!tmp_exists);
+    }
+
+    #[test]
+    fn updates_only_the_named_courier() {
+        let store = SubscriptionStore::default();
+        assert!(store.add("user", subscription("kr.cjlogistics")));
+        assert!(store.add("user", subscription("kr.hanjin")));
+
+        let label =
+            |subscription: &mut Subscription| subscription.label = Some("Shoes".to_string());
+        assert!(store.update("user", "", "1234", label).is_none());
+        assert!(store.update("user", "kr.hanjin", "1234", label).is_some());
+
+        let label_of = |courier_id| store.get("user", courier_id, "1234").unwrap().label;
+        assert_eq!(label_of("kr.hanjin").as_deref(), Some("Shoes"));
+        assert_eq!(label_of("kr.cjlogistics"), None);
+    }
+}